@@ -7,9 +7,11 @@
 // - All commands return Result<..., String> where Err contains a human-readable error.
 
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -21,6 +23,22 @@ fn ping() -> Result<String, String> {
     Ok("pong".to_string())
 }
 
+/// Check whether `json` parses, returning `{ valid: true }` or
+/// `{ valid: false, error, line, column }` (1-based position of the error).
+#[tauri::command]
+fn validate_json(json: &str) -> Result<String, String> {
+    let out = match serde_json::from_str::<serde_json::Value>(json) {
+        Ok(_) => json!({ "valid": true }),
+        Err(e) => json!({
+            "valid": false,
+            "error": e.to_string(),
+            "line": e.line(),
+            "column": e.column(),
+        }),
+    };
+    Ok(out.to_string())
+}
+
 /// Determine the base directory to store app data.
 /// Preferred sources (in order):
 /// - XDG_DATA_HOME (if set)
@@ -28,7 +46,15 @@ fn ping() -> Result<String, String> {
 /// - On Windows: %APPDATA%
 /// - Fallback: $HOME/.local/share
 /// The folder `focosx_desktop` is appended to the chosen base.
+///
+/// The directory is created if missing, so callers always get an existing folder.
 fn base_dir() -> Result<PathBuf, String> {
+    let dir = base_dir_path()?;
+    ensure_dir(&dir)?;
+    Ok(dir)
+}
+
+fn base_dir_path() -> Result<PathBuf, String> {
     // Prefer a simple, user-visible central folder per OS so vault metadata
     // is easy to find. On Linux use ~/.focosx, on macOS use
     // ~/Library/Application Support/focosx, on Windows use %APPDATA%/focosx.
@@ -84,12 +110,62 @@ fn ensure_dir(path: &Path) -> Result<(), String> {
     fs::create_dir_all(path).map_err(|e| format!("failed to create dir {}: {}", path.display(), e))
 }
 
+/// Result of `write_text_file`: either the content hit the disk, or the file
+/// already held identical content and the write was skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteOutcome {
+    Written,
+    WriteSkipped,
+}
+
 /// Write text to a file (overwrites). Ensure parent directory exists.
-fn write_text_file(path: &Path, content: &str) -> Result<(), String> {
+/// If the file already contains exactly `content` nothing is written, so
+/// auto-save on an unmodified file doesn't touch mtime or trigger watchers.
+/// The data goes to a uniquely named hidden sibling first and is then renamed
+/// over `path`, so readers never see a half-written file.
+fn write_text_file(path: &Path, content: &str) -> Result<WriteOutcome, String> {
+    if let Ok(meta) = fs::metadata(path) {
+        if meta.is_file() && meta.len() == content.len() as u64 {
+            if let Ok(existing) = fs::read(path) {
+                if existing == content.as_bytes() {
+                    return Ok(WriteOutcome::WriteSkipped);
+                }
+            }
+        }
+    }
     if let Some(parent) = path.parent() {
         ensure_dir(parent)?;
     }
-    fs::write(path, content).map_err(|e| format!("write error {}: {}", path.display(), e))
+    let (tmp, file) = create_temp_sibling(path).map_err(|e| format!("write error {}: {}", path.display(), e))?;
+    // `f` is dropped (closing the handle) before the rename, which Windows requires
+    let written = {
+        use std::io::Write;
+        let mut f = file;
+        f.write_all(content.as_bytes()).and_then(|_| f.sync_all())
+    };
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("write error {}: {}", path.display(), e));
+    }
+    Ok(WriteOutcome::Written)
+}
+
+/// Create a new `.<name>.<pid>.<n>.tmp` file next to `path`. The name is
+/// unique per process and call, and an existing file is never reused, so
+/// concurrent writers (or a user's own `*.tmp` file) can't clobber each other.
+fn create_temp_sibling(path: &Path) -> std::io::Result<(PathBuf, fs::File)> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    loop {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp = path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), n));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&tmp) {
+            Ok(file) => return Ok((tmp, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 /// Read a file into a String. If file missing, return empty string (frontend will treat as empty).
@@ -101,9 +177,28 @@ fn read_text_file(path: &Path) -> Result<String, String> {
     }
 }
 
+/// `read_text_file` on a worker thread, giving up after `timeout` so an
+/// unresponsive network mount can't freeze the caller. The worker is left to
+/// finish (or hang) on its own.
+fn read_text_file_with_timeout(path: &Path, timeout: std::time::Duration) -> Result<String, String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let owned = path.to_path_buf();
+    std::thread::spawn(move || {
+        let _ = tx.send(read_text_file(&owned));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(_) => Err(format!(
+            "ReadTimeout: {} did not respond within {}ms",
+            path.display(),
+            timeout.as_millis()
+        )),
+    }
+}
+
 /// Convenience: write JSON string to file.
 fn write_json_file(path: &Path, json_str: &str) -> Result<(), String> {
-    write_text_file(path, json_str)
+    write_text_file(path, json_str).map(|_| ())
 }
 
 /// Convenience: read JSON file -> String (empty string if not found)
@@ -111,6 +206,89 @@ fn read_json_file(path: &Path) -> Result<String, String> {
     read_text_file(path)
 }
 
+/// Lexically resolve `.` and `..` components of a path without touching the disk.
+fn normalize_path(path: &Path) -> PathBuf {
+    use std::path::Component;
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Canonicalize a path that may not exist yet: the deepest existing ancestor
+/// is canonicalized (resolving symlinks) and the remaining components are appended.
+/// The path is not normalized first: `link/..` must be resolved by the OS,
+/// which follows `link` before applying `..`. A `..` in the part that doesn't
+/// exist yet is rejected, since its meaning depends on what gets created.
+fn canonicalize_lenient(path: &Path) -> Result<PathBuf, String> {
+    use std::path::Component;
+    let components: Vec<Component> = path.components().collect();
+    for split in (1..=components.len()).rev() {
+        let existing: PathBuf = components[..split].iter().collect();
+        let Ok(mut canonical) = existing.canonicalize() else {
+            continue;
+        };
+        for part in &components[split..] {
+            match part {
+                Component::Normal(name) => canonical.push(name),
+                Component::CurDir => {}
+                _ => return Err(format!("failed to resolve path {}", path.display())),
+            }
+        }
+        return Ok(canonical);
+    }
+    Err(format!("failed to resolve path {}", path.display()))
+}
+
+/// Verify that `path` stays inside `root` once `..` and symlinks are resolved.
+fn ensure_path_within(root: &Path, path: &Path) -> Result<(), String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("failed to resolve {}: {}", root.display(), e))?;
+    if canonicalize_lenient(path)?.starts_with(&root) {
+        Ok(())
+    } else {
+        Err(format!("PathEscapeAttempt: {} is outside {}", path.display(), root.display()))
+    }
+}
+
+/// Verify that `path`, once `..` and symlinks are resolved, lies inside one of
+/// `allowed_roots`. Roots that cannot be resolved are ignored.
+fn check_path_safety(path: &Path, allowed_roots: &[&Path]) -> Result<(), String> {
+    let resolved = canonicalize_lenient(path)?;
+    let allowed = allowed_roots
+        .iter()
+        .filter_map(|r| r.canonicalize().ok())
+        .any(|r| resolved.starts_with(r));
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("path not allowed: {}", path.display()))
+    }
+}
+
+/// `check_path_safety` against the app data dir and every filesystem vault,
+/// for commands that take raw paths from the frontend.
+fn check_command_path(path: &Path) -> Result<(), String> {
+    let base = base_dir()?;
+    let vault_paths: Vec<PathBuf> = read_vaults()?
+        .iter()
+        .filter_map(|v| v.get("path").and_then(|x| x.as_str()))
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .collect();
+    let mut roots: Vec<&Path> = vec![base.as_path()];
+    roots.extend(vault_paths.iter().map(|p| p.as_path()));
+    check_path_safety(path, &roots)
+}
+
 /// Attempt to locate a vault folder (absolute path) that contains a node
 /// with the provided `file_id` in its tree. Returns `Some(PathBuf)` when the
 /// vault folder is absolute and contains the node; otherwise `None`.
@@ -176,25 +354,101 @@ fn find_vault_folder_for_file(file_id: &str) -> Result<Option<PathBuf>, String>
 
 // ----------------- Vaults -----------------
 
+/// Load the app-managed vaults.json as an array (empty if the file is missing).
+fn read_vaults() -> Result<Vec<serde_json::Value>, String> {
+    let mut base = base_dir()?;
+    base.push("vaults.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+/// Write the vault array back to vaults.json.
+fn write_vaults(vaults: &[serde_json::Value]) -> Result<(), String> {
+    let mut base = base_dir()?;
+    base.push("vaults.json");
+    let s = serde_json::to_string_pretty(vaults).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)
+}
+
+/// Look up the `path` of a vault in vaults.json. Returns `None` when the
+/// vault is unknown or has no path.
+fn find_vault_path(vault_id: &str) -> Result<Option<PathBuf>, String> {
+    let arr = read_vaults()?;
+    Ok(arr
+        .iter()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .and_then(|v| v.get("path").and_then(|x| x.as_str()))
+        .map(PathBuf::from))
+}
+
 /// Get vaults.json (returns JSON array string). If missing, return an empty array.
+/// When `sort_key` is `"last_opened_desc"` the vaults are ordered by their
+/// `lastOpenedAt` timestamp, most recent first (never-opened vaults go last).
 #[tauri::command]
-fn get_vaults() -> Result<String, String> {
+fn get_vaults(sort_key: Option<String>) -> Result<String, String> {
     let mut base = base_dir()?;
-    ensure_dir(&base)?;
     base.push("vaults.json");
     let content = read_json_file(&base)?;
     if content.trim().is_empty() {
         // Return empty array - user should create vaults explicitly
-        Ok("[]".to_string())
+        return Ok("[]".to_string());
+    }
+    let icons = read_vault_icons()?;
+    if icons.is_empty() && sort_key.is_none() {
+        return Ok(content);
+    }
+    let mut arr: Vec<serde_json::Value> = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    if sort_key.as_deref() == Some("last_opened_desc") {
+        arr.sort_by_key(|v| std::cmp::Reverse(v.get("lastOpenedAt").and_then(|x| x.as_i64()).unwrap_or(i64::MIN)));
+    }
+    // merge emoji icons from vault_icons.json so the frontend has a single source
+    for v in arr.iter_mut() {
+        let emoji = v.get("id").and_then(|x| x.as_str()).and_then(|id| icons.get(id)).cloned();
+        if let (Some(emoji), Some(obj)) = (emoji, v.as_object_mut()) {
+            obj.insert("emoji".to_string(), json!(emoji));
+        }
+    }
+    serde_json::to_string(&arr).map_err(|e| e.to_string())
+}
+
+/// Load the vault_id -> emoji map from vault_icons.json (empty if missing).
+fn read_vault_icons() -> Result<HashMap<String, String>, String> {
+    let mut base = base_dir()?;
+    base.push("vault_icons.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+/// Return the emoji icon configured for a vault, if any.
+#[tauri::command]
+fn get_emoji_for_vault(vault_id: &str) -> Result<Option<String>, String> {
+    Ok(read_vault_icons()?.get(vault_id).cloned())
+}
+
+/// Set the emoji icon shown for a vault in the sidebar. An empty `emoji` clears it.
+#[tauri::command]
+fn set_emoji_for_vault(vault_id: &str, emoji: &str) -> Result<(), String> {
+    let mut icons = read_vault_icons()?;
+    if emoji.trim().is_empty() {
+        icons.remove(vault_id);
     } else {
-        Ok(content)
+        icons.insert(vault_id.to_string(), emoji.to_string());
     }
+    let mut base = base_dir()?;
+    base.push("vault_icons.json");
+    let s = serde_json::to_string_pretty(&icons).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)
 }
 
 #[tauri::command]
 fn save_vaults(json: String) -> Result<(), String> {
     let mut base = base_dir()?;
-    ensure_dir(&base)?;
     base.push("vaults.json");
     write_json_file(&base, &json)
 }
@@ -213,7 +467,6 @@ fn select_vault_folder() -> Result<String, String> {
 fn create_vault_at_path(name: &str, path: &str) -> Result<String, String> {
     // Update app-managed vaults.json
     let mut base = base_dir()?;
-    ensure_dir(&base)?;
     base.push("vaults.json");
     let raw = read_json_file(&base)?;
     let mut arr: Vec<serde_json::Value> = if raw.trim().is_empty() {
@@ -239,6 +492,150 @@ fn create_vault_at_path(name: &str, path: &str) -> Result<String, String> {
     Ok(id)
 }
 
+/// Change the display name of a vault. The name is trimmed and must not be empty.
+#[tauri::command]
+fn rename_vault(vault_id: &str, new_name: &str) -> Result<(), String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("Vault name must not be empty".to_string());
+    }
+    let mut arr = read_vaults()?;
+    let v = arr
+        .iter_mut()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .ok_or_else(|| format!("vault not found: {}", vault_id))?;
+    if let Some(obj) = v.as_object_mut() {
+        obj.insert("name".to_string(), json!(new_name));
+    }
+    write_vaults(&arr)
+}
+
+/// Record that a vault was just opened by stamping `lastOpenedAt` (ms) on its entry.
+/// The frontend calls this on every vault switch.
+#[tauri::command]
+fn update_vault_last_opened(vault_id: &str) -> Result<(), String> {
+    let mut arr = read_vaults()?;
+    let v = arr
+        .iter_mut()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .ok_or_else(|| format!("vault not found: {}", vault_id))?;
+    if let Some(obj) = v.as_object_mut() {
+        obj.insert("lastOpenedAt".to_string(), json!(chrono::Utc::now().timestamp_millis()));
+    }
+    write_vaults(&arr)
+}
+
+/// Return the `lastOpenedAt` timestamp (ms) of a vault, or `None` if it was never opened.
+#[tauri::command]
+fn get_vault_last_opened(vault_id: &str) -> Result<Option<i64>, String> {
+    let arr = read_vaults()?;
+    let v = arr
+        .iter()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .ok_or_else(|| format!("vault not found: {}", vault_id))?;
+    Ok(v.get("lastOpenedAt").and_then(|x| x.as_i64()))
+}
+
+/// Create the folders of a scanned (folders-only) tree under `dest`.
+fn create_folder_tree(nodes: &[FileSystemNode], dest: &Path) -> Result<(), String> {
+    for n in nodes {
+        let rel = n.id.split_once(':').map(|(_, r)| r).unwrap_or(&n.id);
+        ensure_dir(&dest.join(rel))?;
+        if let Some(children) = &n.children {
+            create_folder_tree(children, dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy only the folder hierarchy of an existing vault into `dest_folder`
+/// (no files), register it as a new vault and return the new vault ID.
+#[tauri::command]
+fn clone_vault_structure(source_vault_id: &str, dest_folder: String, dest_vault_name: &str) -> Result<String, String> {
+    let src = vault_root(source_vault_id)?;
+    let dest = PathBuf::from(&dest_folder);
+    if !dest.is_absolute() {
+        return Err("destination folder must be an absolute path".to_string());
+    }
+    if dest.exists() && fs::read_dir(&dest).map_err(|e| e.to_string())?.next().is_some() {
+        return Err(format!("destination folder is not empty: {}", dest.display()));
+    }
+
+    let opts = configured_scan_options(
+        &src,
+        &ScanOptions {
+            include_files: false,
+            ..Default::default()
+        },
+    );
+    let folders = scan_directory(&src, &src, None, &format!("{}:", source_vault_id), &opts)?;
+    ensure_dir(&dest)?;
+    create_folder_tree(&folders, &dest)?;
+    create_vault_at_path(dest_vault_name, &dest_folder)
+}
+
+/// Copy a filesystem vault's files and folders (without its `.focosx`
+/// metadata) to the new folder `dest_folder_path`, register the copy as
+/// `new_name` and return the new vault ID.
+#[tauri::command]
+fn copy_vault(src_vault_id: &str, dest_folder_path: String, new_name: &str) -> Result<String, String> {
+    let src = vault_root(src_vault_id)?;
+    let dest = PathBuf::from(&dest_folder_path);
+    if !dest.is_absolute() {
+        return Err("destination folder must be an absolute path".to_string());
+    }
+    if dest.exists() {
+        return Err(format!("destination already exists: {}", dest.display()));
+    }
+    if canonicalize_lenient(&dest)?.starts_with(src.canonicalize().map_err(|e| e.to_string())?) {
+        return Err("destination folder must not be inside the source vault".to_string());
+    }
+
+    ensure_dir(&dest)?;
+    let copied = fs::read_dir(&src)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter(|e| e.file_name() != ".focosx")
+        .try_for_each(|e| copy_recursive(&e.path(), &dest.join(e.file_name())));
+    if let Err(e) = copied {
+        // the folder did not exist before, so nothing of the user's is lost
+        let _ = fs::remove_dir_all(&dest);
+        return Err(e);
+    }
+    create_vault_at_path(new_name, &dest_folder_path)
+}
+
+/// Maximum length (in characters) of a vault description.
+const MAX_VAULT_DESCRIPTION_CHARS: usize = 500;
+
+/// Store a plain-text description shown on the vault card (`description` in vaults.json).
+#[tauri::command]
+fn set_vault_description(vault_id: &str, description: String) -> Result<(), String> {
+    if description.chars().count() > MAX_VAULT_DESCRIPTION_CHARS {
+        return Err(format!("description exceeds {} characters", MAX_VAULT_DESCRIPTION_CHARS));
+    }
+    let mut arr = read_vaults()?;
+    let v = arr
+        .iter_mut()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .ok_or_else(|| format!("vault not found: {}", vault_id))?;
+    if let Some(obj) = v.as_object_mut() {
+        obj.insert("description".to_string(), json!(description));
+    }
+    write_vaults(&arr)
+}
+
+/// Return a vault's description (empty string if none was set).
+#[tauri::command]
+fn get_vault_description(vault_id: &str) -> Result<String, String> {
+    let arr = read_vaults()?;
+    let v = arr
+        .iter()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .ok_or_else(|| format!("vault not found: {}", vault_id))?;
+    Ok(v.get("description").and_then(|x| x.as_str()).unwrap_or_default().to_string())
+}
+
 // ----------------- Trees -----------------
 
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
@@ -253,19 +650,406 @@ struct FileSystemNode {
     content: Option<String>,
     #[serde(rename = "parentId")]
     parent_id: Option<String>,
+    /// FOLDER nodes only: number of files anywhere below the folder.
+    #[serde(rename = "fileCount", skip_serializing_if = "Option::is_none")]
+    file_count: Option<usize>,
+    /// Last modification, in ms since the Unix epoch. Only filled when
+    /// scanning with `include_metadata`.
+    #[serde(rename = "modifiedMs", default, skip_serializing_if = "Option::is_none")]
+    modified_ms: Option<i64>,
+    /// Size in bytes; files only, and only with `include_metadata`.
+    #[serde(rename = "sizeBytes", default, skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+    /// File extension without the dot (`"md"`, `"canvas"`); `None` for folders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    extension: Option<String>,
+    /// CANVAS nodes only: number of entries in the canvas' top-level `nodes` array.
+    #[serde(rename = "cardCount", default, skip_serializing_if = "Option::is_none")]
+    card_count: Option<usize>,
+}
+
+/// Knobs for `scan_directory`. `Default` gives the full tree used by `load_tree`.
+#[derive(Clone)]
+struct ScanOptions {
+    /// When false only FOLDER nodes are collected (used by the folder picker).
+    include_files: bool,
+    /// When set, only files with one of these extensions (case-insensitive,
+    /// leading dot optional) are collected. Folders are always kept.
+    filter_extensions: Option<Vec<String>>,
+    /// Stat every entry to fill `modified_ms` / `size_bytes`. Off by default
+    /// since it doubles the syscalls of a plain structure scan.
+    include_metadata: bool,
+    /// Checked before each directory; when set the scan fails with `ScanCancelled`.
+    cancel: Option<CancellationToken>,
+    /// Entries whose name matches one of these patterns (`*` / `?` wildcards)
+    /// are skipped, e.g. `node_modules` or `*.tmp`.
+    exclude_patterns: Vec<String>,
+    /// Folders this many levels below the root (0 = entries of the root) are
+    /// returned with `children: None` instead of being descended into.
+    max_depth: Option<usize>,
+    /// When false, symlinks become `SYMLINK` nodes and are never followed.
+    /// When true they are scanned like their targets, failing on cycles.
+    follow_symlinks: bool,
+    /// Show dot-prefixed entries. `.focosx` is always skipped.
+    include_hidden: bool,
+    /// Return every node in one list (each folder followed by its contents)
+    /// with `children: None`; `parent_id` still links them up.
+    flat: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            include_files: true,
+            filter_extensions: None,
+            include_metadata: false,
+            cancel: None,
+            exclude_patterns: Vec::new(),
+            max_depth: None,
+            follow_symlinks: false,
+            include_hidden: false,
+            flat: false,
+        }
+    }
 }
 
-fn scan_directory(root: &Path, current: &Path, parent_id: Option<String>, id_prefix: &str) -> Result<Vec<FileSystemNode>, String> {
+/// Shared flag used to abort a running scan from another command.
+#[derive(Clone, Default)]
+struct CancellationToken(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+impl CancellationToken {
+    fn cancel(&self) {
+        self.0.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// Token of the scan currently running for each vault.
+#[derive(Default)]
+struct ScanCancellation(Mutex<HashMap<String, CancellationToken>>);
+
+impl ScanCancellation {
+    /// Register a fresh token for `vault_id`, replacing any previous one.
+    fn start(&self, vault_id: &str) -> Result<CancellationToken, String> {
+        let token = CancellationToken::default();
+        self.0.lock().map_err(|e| e.to_string())?.insert(vault_id.to_string(), token.clone());
+        Ok(token)
+    }
+
+    /// Drop `token` once its scan is over, unless a newer scan replaced it.
+    fn finish(&self, vault_id: &str, token: &CancellationToken) {
+        if let Ok(mut map) = self.0.lock() {
+            if map.get(vault_id).map(|t| std::sync::Arc::ptr_eq(&t.0, &token.0)).unwrap_or(false) {
+                map.remove(vault_id);
+            }
+        }
+    }
+}
+
+/// Abort the `load_tree` scan running for a vault, if any. That scan then
+/// fails with a `ScanCancelled` error instead of returning a partial tree.
+#[tauri::command]
+fn cancel_vault_scan(scans: tauri::State<'_, ScanCancellation>, vault_id: &str) -> Result<(), String> {
+    if let Some(token) = scans.0.lock().map_err(|e| e.to_string())?.get(vault_id) {
+        token.cancel();
+    }
+    Ok(())
+}
+
+impl ScanOptions {
+    fn is_excluded(&self, name: &str) -> bool {
+        self.exclude_patterns.iter().any(|p| wildcard_match(p, name))
+    }
+
+    fn extension_allowed(&self, path: &Path) -> bool {
+        let filter = match &self.filter_extensions {
+            Some(f) => f,
+            None => return true,
+        };
+        let ext = match path.extension() {
+            Some(e) => e.to_string_lossy().to_lowercase(),
+            None => return false,
+        };
+        filter.iter().any(|f| f.trim_start_matches('.').to_lowercase() == ext)
+    }
+}
+
+/// Match `name` against a pattern where `*` matches any run of characters and
+/// `?` exactly one character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((bp, bn)) = backtrack {
+            pi = bp + 1;
+            ni = bn + 1;
+            backtrack = Some((bp, bn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Per-vault settings stored in `<vault>/.focosx/config.json`. Unknown keys
+/// are ignored here and preserved by `save_vault_config`.
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct VaultConfig {
+    exclude_patterns: Vec<String>,
+    max_scan_depth: Option<usize>,
+    include_hidden: bool,
+    /// Snapshots kept per file by `save_file_content`; 0 turns history off.
+    max_file_versions: Option<usize>,
+}
+
+fn vault_config_path(root: &Path) -> PathBuf {
+    let mut p = root.to_path_buf();
+    p.push(".focosx");
+    p.push("config.json");
+    p
+}
+
+/// Read a vault's config, falling back to defaults when it is missing or invalid.
+fn read_vault_config(root: &Path) -> VaultConfig {
+    read_vault_config_file(&vault_config_path(root))
+}
+
+fn read_vault_config_file(path: &Path) -> VaultConfig {
+    let raw = read_json_file(path).unwrap_or_default();
+    serde_json::from_str(&raw).unwrap_or_default()
+}
+
+/// `opts` with the scan settings from the vault's config applied, so every
+/// vault-wide walk skips the same entries as `load_tree`.
+fn configured_scan_options(root: &Path, opts: &ScanOptions) -> ScanOptions {
+    let config = read_vault_config(root);
+    ScanOptions {
+        exclude_patterns: config.exclude_patterns,
+        max_depth: config.max_scan_depth,
+        include_hidden: config.include_hidden,
+        ..opts.clone()
+    }
+}
+
+/// Config file of any vault: `.focosx/config.json` in a filesystem vault,
+/// `configs/<vaultId>.json` in the app data dir otherwise.
+fn vault_config_file(vault_id: &str) -> Result<PathBuf, String> {
+    match find_vault_path(vault_id)?.filter(|p| p.is_absolute()) {
+        Some(root) => Ok(vault_config_path(&root)),
+        None => {
+            let mut base = base_dir()?;
+            base.push("configs");
+            base.push(format!("{}.json", vault_id));
+            Ok(base)
+        }
+    }
+}
+
+/// Return the vault's config object (`{}` when missing). The schema belongs
+/// to the frontend; Rust only reads the scan settings from it.
+#[tauri::command]
+fn load_vault_config(vault_id: &str) -> Result<String, String> {
+    let raw = read_json_file(&vault_config_file(vault_id)?)?;
+    if raw.trim().is_empty() {
+        return Ok("{}".to_string());
+    }
+    Ok(raw)
+}
+
+/// Replace the vault's config. `json` must be a JSON object.
+#[tauri::command]
+fn save_vault_config(vault_id: &str, json: String) -> Result<(), String> {
+    let value: serde_json::Value = serde_json::from_str(&json).map_err(|e| format!("invalid config: {}", e))?;
+    if !value.is_object() {
+        return Err("invalid config: expected a JSON object".to_string());
+    }
+    let out = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+    write_json_file(&vault_config_file(vault_id)?, &out)
+}
+
+/// Read `<src>/.obsidian/<name>` as JSON, or `Null` when it is missing or invalid.
+fn read_obsidian_json(src: &Path, name: &str) -> serde_json::Value {
+    let mut p = src.to_path_buf();
+    p.push(".obsidian");
+    p.push(name);
+    fs::read_to_string(&p)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Register an existing Obsidian vault folder as a filesystem vault and
+/// translate the settings FocosX understands from `.obsidian/app.json` and
+/// `.obsidian/workspace.json` into `.focosx/config.json`. Notes and the
+/// `.obsidian` folder are left untouched. Returns the new vault id.
+#[tauri::command]
+fn import_from_obsidian_vault(src_path: &str, vault_name: &str) -> Result<String, String> {
+    let src = PathBuf::from(src_path);
+    if !src.is_absolute() {
+        return Err(format!("expected an absolute path, got {}", src_path));
+    }
+    if !src.is_dir() {
+        return Err(format!("vault folder not found: {}", src_path));
+    }
+
+    let app = read_obsidian_json(&src, "app.json");
+    let workspace = read_obsidian_json(&src, "workspace.json");
+
+    // keep whatever a previous import or the user already configured
+    let config_path = vault_config_path(&src);
+    let mut config: serde_json::Map<String, serde_json::Value> = read_json_file(&config_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+
+    // Obsidian ignore filters are path prefixes ("Archive/") or regexes
+    // ("/^tmp/"); our patterns match single names, so keep the last segment
+    // of plain paths and drop regexes.
+    if let Some(filters) = app.get("userIgnoreFilters").and_then(|v| v.as_array()) {
+        let mut patterns: Vec<String> = config
+            .get("excludePatterns")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        for f in filters.iter().filter_map(|f| f.as_str()) {
+            if f.len() > 1 && f.starts_with('/') && f.ends_with('/') {
+                continue;
+            }
+            if let Some(name) = f.trim_matches('/').rsplit('/').next().filter(|n| !n.is_empty()) {
+                if !patterns.iter().any(|p| p == name) {
+                    patterns.push(name.to_string());
+                }
+            }
+        }
+        config.insert("excludePatterns".to_string(), json!(patterns));
+    }
+    for (obsidian_key, key) in [("attachmentFolderPath", "attachmentFolder"), ("newFileFolderPath", "newNoteFolder")] {
+        if let Some(v) = app.get(obsidian_key).and_then(|v| v.as_str()) {
+            config.insert(key.to_string(), json!(v));
+        }
+    }
+    if let Some(files) = workspace.get("lastOpenFiles").and_then(|v| v.as_array()) {
+        let files: Vec<&str> = files.iter().filter_map(|f| f.as_str()).collect();
+        config.insert("lastOpenFiles".to_string(), json!(files));
+    }
+
+    let id = create_vault_at_path(vault_name, src_path)?;
+    if !config.is_empty() {
+        let out = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+        write_json_file(&config_path, &out)?;
+    }
+    Ok(id)
+}
+
+/// Canvases larger than this are not parsed during a scan.
+const CANVAS_CARD_COUNT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Length of the top-level `nodes` array of a `.canvas` file, or `None` when
+/// the file is too large or not valid canvas JSON.
+fn canvas_card_count(path: &Path) -> Option<usize> {
+    if fs::metadata(path).ok()?.len() > CANVAS_CARD_COUNT_MAX_BYTES {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    value.get("nodes")?.as_array().map(|a| a.len())
+}
+
+/// An entry skipped by `scan_directory` that the user should hear about.
+struct ScanWarning {
+    raw_path: String,
+    reason: &'static str,
+}
+
+/// Mutable state shared by one `scan_directory` run.
+#[derive(Default)]
+struct ScanState {
+    warnings: Vec<ScanWarning>,
+    /// Canonical paths of the folders currently being scanned (root to
+    /// `current`); only tracked with `follow_symlinks`, to detect cycles.
+    ancestors: HashSet<PathBuf>,
+}
+
+fn scan_directory(root: &Path, current: &Path, parent_id: Option<String>, id_prefix: &str, opts: &ScanOptions) -> Result<Vec<FileSystemNode>, String> {
+    scan_directory_with_warnings(root, current, parent_id, id_prefix, opts).map(|(nodes, _)| nodes)
+}
+
+/// Like `scan_directory`, but also returns the entries that were skipped with a warning.
+fn scan_directory_with_warnings(root: &Path, current: &Path, parent_id: Option<String>, id_prefix: &str, opts: &ScanOptions) -> Result<(Vec<FileSystemNode>, Vec<ScanWarning>), String> {
+    let mut state = ScanState::default();
+    let (nodes, _) = scan_directory_counted(root, current, parent_id, id_prefix, opts, &mut state)?;
+    Ok((nodes, state.warnings))
+}
+
+/// Emit one `vault-scan-warning` event per skipped entry.
+fn emit_scan_warnings(app: &tauri::AppHandle, vault_id: &str, warnings: &[ScanWarning]) {
+    for w in warnings {
+        let _ = app.emit(
+            "vault-scan-warning",
+            json!({ "vault_id": vault_id, "raw_path": w.raw_path, "reason": w.reason }),
+        );
+    }
+}
+
+/// Recursive body of `scan_directory`; also returns the number of files found
+/// below `current` so folder nodes can carry a `fileCount` without a second pass.
+/// Files hidden only because `include_files` is false are still counted.
+fn scan_directory_counted(
+    root: &Path,
+    current: &Path,
+    parent_id: Option<String>,
+    id_prefix: &str,
+    opts: &ScanOptions,
+    state: &mut ScanState,
+) -> Result<(Vec<FileSystemNode>, usize), String> {
+    if opts.cancel.as_ref().map(|c| c.is_cancelled()).unwrap_or(false) {
+        return Err(format!("ScanCancelled: scan of {} was cancelled", root.display()));
+    }
+    let canonical = if opts.follow_symlinks {
+        Some(current.canonicalize().map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+    if let Some(c) = &canonical {
+        if !state.ancestors.insert(c.clone()) {
+            return Err(format!("SymlinkCycle: {} links back to one of its parent folders", current.display()));
+        }
+    }
     let mut nodes = Vec::new();
+    let mut file_count = 0;
+    let depth = current.strip_prefix(root).map(|p| p.components().count()).unwrap_or(0);
+    let descend = opts.max_depth.map(|max| depth < max).unwrap_or(true);
     let entries = fs::read_dir(current).map_err(|e| e.to_string())?;
 
     for entry in entries {
         let entry = entry.map_err(|e| e.to_string())?;
         let path = entry.path();
-        let name = entry.file_name().to_string_lossy().to_string();
+        // Lossy conversion would corrupt the name and ID (and the file on the
+        // next write), so entries whose names aren't valid UTF-8 are skipped.
+        let name = match entry.file_name().to_str() {
+            Some(n) => n.to_string(),
+            None => {
+                state.warnings.push(ScanWarning {
+                    raw_path: path.to_string_lossy().to_string(),
+                    reason: "non_utf8_filename",
+                });
+                continue;
+            }
+        };
 
-        // Skip hidden files/folders (like .focosx, .git, .DS_Store)
-        if name.starts_with('.') {
+        // Skip hidden files/folders (like .git, .DS_Store) unless asked for;
+        // .focosx is app metadata and never shown
+        if name == ".focosx" || (name.starts_with('.') && !opts.include_hidden) || opts.is_excluded(&name) {
             continue;
         }
 
@@ -273,7 +1057,36 @@ fn scan_directory(root: &Path, current: &Path, parent_id: Option<String>, id_pre
         let raw_id = relative_path.to_string_lossy().to_string().replace("\\", "/");
         let id = format!("{}{}", id_prefix, raw_id);
         
+        let is_symlink = entry.file_type().map(|t| t.is_symlink()).unwrap_or(false);
+        if is_symlink && !opts.follow_symlinks {
+            if opts.include_files && opts.filter_extensions.is_none() {
+                nodes.push(FileSystemNode {
+                    id,
+                    name,
+                    node_type: "SYMLINK".to_string(),
+                    children: None,
+                    content: None,
+                    parent_id: parent_id.clone(),
+                    file_count: None,
+                    modified_ms: None,
+                    size_bytes: None,
+                    extension: None,
+                    card_count: None,
+                });
+            }
+            continue;
+        }
+
         let is_dir = path.is_dir();
+        if !is_dir {
+            if !opts.extension_allowed(&path) {
+                continue;
+            }
+            file_count += 1;
+            if !opts.include_files {
+                continue;
+            }
+        }
         let node_type = if is_dir {
             "FOLDER".to_string()
         } else if name.ends_with(".canvas") {
@@ -283,10 +1096,16 @@ fn scan_directory(root: &Path, current: &Path, parent_id: Option<String>, id_pre
         };
 
         let mut children = None;
-        if is_dir {
-            children = Some(scan_directory(root, &path, Some(id.clone()), id_prefix)?);
+        let mut folder_file_count = None;
+        if is_dir && descend {
+            let (child_nodes, count) = scan_directory_counted(root, &path, Some(id.clone()), id_prefix, opts, state)?;
+            children = Some(child_nodes);
+            folder_file_count = Some(count);
+            file_count += count;
         }
 
+        let meta = if opts.include_metadata { entry.metadata().ok() } else { None };
+        let card_count = if node_type == "CANVAS" { canvas_card_count(&path) } else { None };
         nodes.push(FileSystemNode {
             id,
             name,
@@ -294,6 +1113,11 @@ fn scan_directory(root: &Path, current: &Path, parent_id: Option<String>, id_pre
             children,
             content: None, // We don't load content during tree scan
             parent_id: parent_id.clone(),
+            file_count: folder_file_count,
+            modified_ms: meta.as_ref().and_then(modified_ms),
+            size_bytes: meta.as_ref().filter(|m| !m.is_dir()).map(|m| m.len()),
+            extension: if is_dir { None } else { path.extension().map(|e| e.to_string_lossy().to_string()) },
+            card_count,
         });
     }
     
@@ -309,386 +1133,3713 @@ fn scan_directory(root: &Path, current: &Path, parent_id: Option<String>, id_pre
             a.name.cmp(&b.name)
         }
     });
+    if opts.flat {
+        // children are already flat, so one level of splicing is enough
+        nodes = nodes
+            .into_iter()
+            .flat_map(|mut n| {
+                let children = n.children.take().unwrap_or_default();
+                std::iter::once(n).chain(children)
+            })
+            .collect();
+    }
 
-    Ok(nodes)
+    if let Some(c) = &canonical {
+        state.ancestors.remove(c);
+    }
+    Ok((nodes, file_count))
 }
 
+/// Load a vault tree. Pass `include_metadata: true` to get `modifiedMs` and
+/// `sizeBytes` on every scanned node (costs one extra stat per entry).
 #[tauri::command]
-fn load_tree(vault_id: &str) -> Result<String, String> {
+fn load_tree(
+    app: tauri::AppHandle,
+    scans: tauri::State<'_, ScanCancellation>,
+    vault_id: &str,
+    include_metadata: Option<bool>,
+) -> Result<String, String> {
+    let token = scans.start(vault_id)?;
+    let opts = ScanOptions {
+        include_metadata: include_metadata.unwrap_or(false),
+        cancel: Some(token.clone()),
+        ..Default::default()
+    };
+    let result = read_vault_tree(vault_id, &opts);
+    scans.finish(vault_id, &token);
+    let (tree, warnings) = result?;
+    emit_scan_warnings(&app, vault_id, &warnings);
+    Ok(tree)
+}
+
+/// Body of `load_tree`: returns the tree JSON plus any scan warnings.
+fn read_vault_tree(vault_id: &str, opts: &ScanOptions) -> Result<(String, Vec<ScanWarning>), String> {
     eprintln!("[load_tree] called with vault_id={}", vault_id);
-    
-    // If the vault points to an absolute filesystem folder, prefer reading the tree
-    // from a file inside that folder (so vault state can live next to the user's files).
-    let mut base = base_dir()?;
-    let vaults_path = {
-        let mut p = base.clone();
-        p.push("vaults.json");
-        p
-    };
-
-    if let Ok(vraw) = read_json_file(&vaults_path) {
-        if !vraw.trim().is_empty() {
-            if let Ok(vs) = serde_json::from_str::<serde_json::Value>(&vraw) {
-                if let Some(arr) = vs.as_array() {
-                    for v in arr {
-                        if v.get("id").and_then(|x| x.as_str()) == Some(vault_id) {
-                            if let Some(p) = v.get("path").and_then(|x| x.as_str()) {
-                                let candidate = Path::new(p);
-                                eprintln!("[load_tree] Found vault path: {:?}, is_absolute={}, exists={}", candidate, candidate.is_absolute(), candidate.exists());
-                                if candidate.is_absolute() {
-                                    // Use real filesystem scan
-                                    if candidate.exists() {
-                                        let nodes = scan_directory(candidate, candidate, None, &format!("{}:", vault_id))?;
-                                        let result = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
-                                        eprintln!("[load_tree] Scanned {} nodes, result: {}", nodes.len(), &result[..result.len().min(500)]);
-                                        return Ok(result);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+
+    // Filesystem vaults: the tree is derived from the folder on disk.
+    if let Some(folder) = find_vault_path(vault_id)?.filter(|p| p.is_absolute()) {
+        eprintln!("[load_tree] Found vault path: {:?}, exists={}", folder, folder.exists());
+        if folder.exists() {
+            let opts = configured_scan_options(&folder, opts);
+            let (nodes, warnings) = scan_directory_with_warnings(&folder, &folder, None, &format!("{}:", vault_id), &opts)?;
+            let result = serde_json::to_string(&nodes).map_err(|e| e.to_string())?;
+            eprintln!("[load_tree] Scanned {} nodes, result: {}", nodes.len(), &result[..result.len().min(500)]);
+            return Ok((result, warnings));
         }
     }
 
     // Fallback to app-managed trees folder
     eprintln!("[load_tree] Using fallback trees folder");
+    Ok((read_json_file(&app_tree_file(vault_id)?)?, Vec::new()))
+}
+
+/// `trees/<vaultId>.json` in the app data dir, used by vaults without a folder.
+fn app_tree_file(vault_id: &str) -> Result<PathBuf, String> {
+    let mut base = base_dir()?;
     base.push("trees");
     ensure_dir(&base)?;
     base.push(format!("{}.json", vault_id));
-    read_json_file(&base)
+    Ok(base)
 }
 
-#[tauri::command]
-fn save_tree(vault_id: &str, json: String) -> Result<(), String> {
-    // If the vault points to an absolute filesystem folder, do nothing.
-    // The tree is derived from the actual filesystem structure and should
-    // not be saved separately.
-    let mut base = base_dir()?;
-    let vaults_path = {
-        let mut p = base.clone();
-        p.push("vaults.json");
-        p
-    };
-
-    if let Ok(vraw) = read_json_file(&vaults_path) {
-        if !vraw.trim().is_empty() {
-            if let Ok(vs) = serde_json::from_str::<serde_json::Value>(&vraw) {
-                if let Some(arr) = vs.as_array() {
-                    for v in arr {
-                        if v.get("id").and_then(|x| x.as_str()) == Some(vault_id) {
-                            if let Some(p) = v.get("path").and_then(|x| x.as_str()) {
-                                let candidate = Path::new(p);
-                                if candidate.is_absolute() {
-                                    // Real filesystem vault - tree is derived from disk, skip saving
-                                    return Ok(());
-                                }
-                            }
+/// `.focosx/tree.json` inside a vault folder.
+fn vault_folder_tree_file(vault_folder: &Path) -> PathBuf {
+    let mut p = vault_folder.to_path_buf();
+    p.push(".focosx");
+    p.push("tree.json");
+    p
+}
+
+/// Resolve the root folder of a filesystem-backed vault, failing when the
+/// vault is unknown or its folder is missing.
+fn vault_root(vault_id: &str) -> Result<PathBuf, String> {
+    let root = find_vault_path(vault_id)?.ok_or("Vault not found or has no path")?;
+    if !root.is_absolute() || !root.exists() {
+        return Err("Vault path does not exist".to_string());
+    }
+    Ok(root)
+}
+
+/// Return the canonical absolute folder of a filesystem vault. Use this
+/// instead of reading `path` out of `get_vaults` in the frontend.
+#[tauri::command]
+fn get_vault_disk_path(vault_id: &str) -> Result<String, String> {
+    let path = find_vault_path(vault_id)?.ok_or("Vault not found or has no path")?;
+    let canonical = fs::canonicalize(&path).map_err(|e| format!("Vault path {:?} is not accessible: {}", path, e))?;
+    if !canonical.is_dir() {
+        return Err(format!("Vault path {:?} is not a directory", canonical));
+    }
+    Ok(canonical.to_string_lossy().to_string())
+}
+
+/// Convert a path relative to a vault's root into a normalised absolute path,
+/// refusing results that fall outside the vault.
+#[tauri::command]
+fn resolve_vault_relative_path(vault_id: &str, relative: String) -> Result<String, String> {
+    let root = normalize_path(&vault_root(vault_id)?);
+    if Path::new(&relative).is_absolute() {
+        return Err(format!("expected a vault-relative path, got {}", relative));
+    }
+    let resolved = normalize_path(&root.join(&relative));
+    if !resolved.starts_with(&root) {
+        return Err(format!("PathEscapeAttempt: {} is outside the vault", relative));
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+/// Platform-native absolute path of a node (`vaultId:relative/path`). Use
+/// this instead of joining the vault path and relative path in the frontend.
+#[tauri::command]
+fn get_absolute_path_for_node(vault_id: &str, file_id: &str) -> Result<String, String> {
+    let relative = match file_id.split_once(':') {
+        Some((id, rel)) if id == vault_id => rel,
+        _ => return Err(format!("{} is not a node of vault {}", file_id, vault_id)),
+    };
+    let root = vault_root(vault_id)?;
+    let mut path = root.clone();
+    for part in relative.split(['/', '\\']).filter(|p| !p.is_empty()) {
+        path.push(part);
+    }
+    ensure_path_within(&root, &path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Best-guess MIME type from a file extension, for display only.
+fn mime_hint(path: &Path) -> &'static str {
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "md" | "markdown" => "text/markdown",
+        "txt" => "text/plain",
+        "canvas" | "json" => "application/json",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// On-disk details of one node for the file info panel:
+/// `{ path, sizeBytes, modifiedMs, createdMs, isReadOnly, mimeHint }`.
+/// `createdMs` is null where the platform doesn't record it; folders get
+/// `mimeHint: "inode/directory"`.
+#[tauri::command]
+fn get_node_metadata(vault_id: &str, file_id: &str) -> Result<String, String> {
+    let path = PathBuf::from(get_absolute_path_for_node(vault_id, file_id)?);
+    let meta = fs::metadata(&path).map_err(|e| format!("failed to stat {}: {}", path.display(), e))?;
+    let created_ms = meta
+        .created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64);
+    let out = json!({
+        "path": path.to_string_lossy(),
+        "sizeBytes": meta.len(),
+        "modifiedMs": modified_ms(&meta),
+        "createdMs": created_ms,
+        "isReadOnly": meta.permissions().readonly(),
+        "mimeHint": if meta.is_dir() { "inode/directory" } else { mime_hint(&path) },
+    });
+    Ok(out.to_string())
+}
+
+/// Whether a scanned node has readable content (not a folder or an
+/// unfollowed symlink).
+fn is_file_node(n: &FileSystemNode) -> bool {
+    n.node_type != "FOLDER" && n.node_type != "SYMLINK"
+}
+
+/// Flatten a scanned tree into its file nodes (children are dropped; folders
+/// and unfollowed symlinks are skipped).
+fn flatten_files(nodes: Vec<FileSystemNode>, out: &mut Vec<FileSystemNode>) {
+    for mut n in nodes {
+        match n.children.take() {
+            Some(children) => flatten_files(children, out),
+            None if is_file_node(&n) => out.push(n),
+            None => {}
+        }
+    }
+}
+
+/// Absolute path of a scanned node (`vaultId:relative/path`) inside `root`.
+fn node_disk_path(root: &Path, id: &str) -> PathBuf {
+    let mut p = root.to_path_buf();
+    p.push(id.split_once(':').map(|(_, rel)| rel).unwrap_or(id));
+    p
+}
+
+/// Scan a filesystem vault and return its tree, optionally restricted to
+/// files with the given extensions (e.g. `["csv"]` for a CSV viewer plugin).
+#[tauri::command]
+fn scan_directory_cmd(app: tauri::AppHandle, vault_id: &str, filter_extensions: Option<Vec<String>>) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            filter_extensions,
+            ..Default::default()
+        },
+    );
+    let (nodes, warnings) = scan_directory_with_warnings(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    emit_scan_warnings(&app, vault_id, &warnings);
+    serde_json::to_string(&nodes).map_err(|e| e.to_string())
+}
+
+/// Keep only FOLDER nodes (recursively) from a stored tree value.
+fn retain_folder_nodes(nodes: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    nodes
+        .iter()
+        .filter(|n| n.get("type").and_then(|x| x.as_str()) == Some("FOLDER"))
+        .map(|n| {
+            let mut n = n.clone();
+            if let Some(children) = n.get("children").and_then(|c| c.as_array()).map(|c| retain_folder_nodes(c)) {
+                n["children"] = serde_json::Value::Array(children);
+            }
+            n
+        })
+        .collect()
+}
+
+/// Return only the folder hierarchy of a vault (no file nodes). Used by the
+/// folder-picker, which would otherwise have to load the full tree.
+#[tauri::command]
+fn get_vault_folder_structure(vault_id: &str) -> Result<String, String> {
+    if let Some(root) = find_vault_path(vault_id)? {
+        if root.is_absolute() && root.exists() {
+            let opts = configured_scan_options(
+                &root,
+                &ScanOptions {
+                    include_files: false,
+                    ..Default::default()
+                },
+            );
+            let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+            return serde_json::to_string(&nodes).map_err(|e| e.to_string());
+        }
+    }
+
+    // App-managed vault: filter the stored tree
+    let (raw, _) = read_vault_tree(vault_id, &ScanOptions::default())?;
+    if raw.trim().is_empty() {
+        return Ok("[]".to_string());
+    }
+    let tree: Vec<serde_json::Value> = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    serde_json::to_string(&retain_folder_nodes(&tree)).map_err(|e| e.to_string())
+}
+
+#[derive(serde::Serialize)]
+struct TreeStats {
+    max_depth: usize,
+    nodes_per_level: Vec<usize>,
+    total_nodes: usize,
+    total_files: usize,
+    total_folders: usize,
+    deepest_node: String,
+}
+
+impl TreeStats {
+    fn add(&mut self, node: &serde_json::Value, level: usize) {
+        if self.nodes_per_level.len() <= level {
+            self.nodes_per_level.resize(level + 1, 0);
+        }
+        self.nodes_per_level[level] += 1;
+        self.total_nodes += 1;
+        if node.get("type").and_then(|x| x.as_str()) == Some("FOLDER") {
+            self.total_folders += 1;
+        } else {
+            self.total_files += 1;
+        }
+        if level + 1 > self.max_depth {
+            self.max_depth = level + 1;
+            self.deepest_node = node.get("id").and_then(|x| x.as_str()).unwrap_or_default().to_string();
+        }
+        if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+            for child in children {
+                self.add(child, level + 1);
+            }
+        }
+    }
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct VaultStats {
+    file_count: usize,
+    folder_count: usize,
+    total_bytes: u64,
+    last_modified_ms: Option<i64>,
+}
+
+impl VaultStats {
+    fn add(&mut self, node: &serde_json::Value) {
+        if node.get("type").and_then(|x| x.as_str()) == Some("FOLDER") {
+            self.folder_count += 1;
+        } else {
+            self.file_count += 1;
+            // app-managed trees keep content inline instead of on disk
+            self.total_bytes += node
+                .get("sizeBytes")
+                .and_then(|x| x.as_u64())
+                .or_else(|| node.get("content").and_then(|x| x.as_str()).map(|c| c.len() as u64))
+                .unwrap_or(0);
+            if let Some(ms) = node.get("modifiedMs").and_then(|x| x.as_i64()) {
+                self.last_modified_ms = Some(self.last_modified_ms.map_or(ms, |cur| cur.max(ms)));
+            }
+        }
+        if let Some(children) = node.get("children").and_then(|c| c.as_array()) {
+            for child in children {
+                self.add(child);
+            }
+        }
+    }
+}
+
+/// Dashboard summary of a vault: `{ fileCount, folderCount, totalBytes,
+/// lastModifiedMs }`. Uses the same tree as `load_tree`, so excluded entries
+/// are not counted.
+#[tauri::command]
+fn get_vault_stats(vault_id: &str) -> Result<String, String> {
+    let opts = ScanOptions {
+        include_metadata: true,
+        ..Default::default()
+    };
+    let (raw, _) = read_vault_tree(vault_id, &opts)?;
+    let nodes: Vec<serde_json::Value> = if raw.trim().is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(&raw).map_err(|e| e.to_string())?
+    };
+    let mut stats = VaultStats::default();
+    for n in &nodes {
+        stats.add(n);
+    }
+    serde_json::to_string(&stats).map_err(|e| e.to_string())
+}
+
+#[derive(serde::Serialize)]
+struct JsonParseError {
+    path: String,
+    error: String,
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct VaultHealthReport {
+    missing_path: bool,
+    orphaned_content_files: Vec<String>,
+    unreadable_files: Vec<String>,
+    json_parse_errors: Vec<JsonParseError>,
+}
+
+/// Collect every `id` in a nested or flat JSON tree.
+fn collect_tree_ids(nodes: &[serde_json::Value], out: &mut HashSet<String>) {
+    for n in nodes {
+        if let Some(id) = n.get("id").and_then(|x| x.as_str()) {
+            out.insert(id.to_string());
+        }
+        if let Some(children) = n.get("children").and_then(|c| c.as_array()) {
+            collect_tree_ids(children, out);
+        }
+    }
+}
+
+/// Try to parse every `.json` file below `dir` (the trash is skipped).
+fn check_json_files(root: &Path, dir: &Path, errors: &mut Vec<JsonParseError>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if path.is_dir() {
+            if entry.file_name() != "trash" {
+                check_json_files(root, &path, errors);
+            }
+            continue;
+        }
+        if path.extension().map(|e| e != "json").unwrap_or(true) {
+            continue;
+        }
+        let result = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).map(|_| ()).map_err(|e| e.to_string()));
+        if let Err(error) = result {
+            errors.push(JsonParseError { path: rel, error });
+        }
+    }
+}
+
+/// Add the node IDs of a stored tree JSON file. A missing file adds nothing;
+/// an unreadable file or one that isn't a JSON array is an error.
+fn add_tree_file_ids(path: &Path, ids: &mut HashSet<String>) -> Result<(), String> {
+    let raw = read_json_file(path)?;
+    if raw.trim().is_empty() {
+        return Ok(());
+    }
+    match serde_json::from_str(&raw) {
+        Ok(serde_json::Value::Array(arr)) => {
+            collect_tree_ids(&arr, ids);
+            Ok(())
+        }
+        Ok(_) => Err(format!("{} is not a tree (expected a JSON array)", path.display())),
+        Err(e) => Err(format!("failed to parse {}: {}", path.display(), e)),
+    }
+}
+
+/// Content files (`<fileId>.json`) in `dir` whose ID is not in `ids`, sorted.
+fn orphaned_content_files(dir: &Path, ids: &HashSet<String>) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_file())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            !ids.contains(name.strip_suffix(".json").unwrap_or(&name))
+        })
+        .map(|e| e.path())
+        .collect();
+    out.sort();
+    out
+}
+
+/// Delete content files that no tree node refers to any more. Filesystem
+/// vaults are checked against their scanned tree (and legacy `.focosx/tree.json`);
+/// the app-level `contents/` folder is shared, so it is checked against the
+/// trees of all app-managed vaults. Nothing is deleted when any of those
+/// trees can't be read or parsed. Returns `{ deletedCount, deletedBytes }`.
+#[tauri::command]
+fn cleanup_orphaned_content(vault_id: &str) -> Result<String, String> {
+    let mut ids = HashSet::new();
+    let contents_dir = match find_vault_path(vault_id)?.filter(|p| p.is_absolute()) {
+        Some(root) => {
+            if !root.is_dir() {
+                return Err("Vault path does not exist".to_string());
+            }
+            let opts = configured_scan_options(&root, &ScanOptions::default());
+            let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+            let scanned: Vec<serde_json::Value> = nodes.iter().filter_map(|n| serde_json::to_value(n).ok()).collect();
+            collect_tree_ids(&scanned, &mut ids);
+            add_tree_file_ids(&vault_folder_tree_file(&root), &mut ids)?;
+            root.join(".focosx").join("contents")
+        }
+        None => {
+            let base = base_dir()?;
+            let trees_dir = base.join("trees");
+            let entries = fs::read_dir(&trees_dir).map_err(|e| format!("failed to read {}: {}", trees_dir.display(), e))?;
+            for entry in entries {
+                let entry = entry.map_err(|e| format!("failed to read {}: {}", trees_dir.display(), e))?;
+                add_tree_file_ids(&entry.path(), &mut ids)?;
+            }
+            base.join("contents")
+        }
+    };
+
+    let mut deleted_count = 0u64;
+    let mut deleted_bytes = 0u64;
+    for path in orphaned_content_files(&contents_dir, &ids) {
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if fs::remove_file(&path).is_ok() {
+            deleted_count += 1;
+            deleted_bytes += size;
+        }
+    }
+    Ok(json!({ "deletedCount": deleted_count, "deletedBytes": deleted_bytes }).to_string())
+}
+
+/// Inspect a filesystem vault without modifying it. Returns
+/// `{ missingPath, orphanedContentFiles, unreadableFiles, jsonParseErrors }`:
+/// content files in `.focosx/contents/` with no matching tree node, vault
+/// files that can't be opened, and `.focosx/` JSON files that fail to parse.
+#[tauri::command]
+fn vault_health_check(vault_id: &str) -> Result<String, String> {
+    let mut report = VaultHealthReport::default();
+    let root = match find_vault_path(vault_id)?.filter(|p| p.is_absolute() && p.is_dir()) {
+        Some(r) => r,
+        None => {
+            report.missing_path = true;
+            return serde_json::to_string(&report).map_err(|e| e.to_string());
+        }
+    };
+
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &configured_scan_options(&root, &ScanOptions::default()))?;
+    let mut files = Vec::new();
+    flatten_files(nodes.clone(), &mut files);
+    for f in &files {
+        if fs::File::open(node_disk_path(&root, &f.id)).is_err() {
+            report.unreadable_files.push(f.id.clone());
+        }
+    }
+
+    // Content files may belong to scanned nodes or to the legacy .focosx/tree.json
+    let mut ids = HashSet::new();
+    let scanned: Vec<serde_json::Value> = nodes.iter().filter_map(|n| serde_json::to_value(n).ok()).collect();
+    collect_tree_ids(&scanned, &mut ids);
+    // a tree.json that fails to parse shows up in jsonParseErrors below
+    let _ = add_tree_file_ids(&vault_folder_tree_file(&root), &mut ids);
+    report.orphaned_content_files = orphaned_content_files(&root.join(".focosx").join("contents"), &ids)
+        .iter()
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect();
+
+    check_json_files(&root, &root.join(".focosx"), &mut report.json_parse_errors);
+    serde_json::to_string(&report).map_err(|e| e.to_string())
+}
+
+/// Diagnostic summary of a vault tree's shape: `{ max_depth, nodes_per_level,
+/// total_nodes, total_files, total_folders, deepest_node }`. Works for nested
+/// trees (filesystem vaults) and flat `parentId` lists (app-managed vaults).
+#[tauri::command]
+fn get_tree_stats(vault_id: &str) -> Result<String, String> {
+    let (raw, _) = read_vault_tree(vault_id, &ScanOptions::default())?;
+    let nodes: Vec<serde_json::Value> = if raw.trim().is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(&raw).map_err(|e| e.to_string())?
+    };
+
+    // Flat lists encode depth through parentId chains
+    let parents: HashMap<&str, &str> = nodes
+        .iter()
+        .filter_map(|n| Some((n.get("id")?.as_str()?, n.get("parentId")?.as_str()?)))
+        .collect();
+    let level_of = |n: &serde_json::Value| {
+        let mut level = 0;
+        let mut cur = n.get("id").and_then(|x| x.as_str());
+        while let Some(parent) = cur.and_then(|id| parents.get(id)) {
+            level += 1;
+            cur = Some(parent);
+            if level > nodes.len() {
+                break; // cycle guard
+            }
+        }
+        level
+    };
+
+    let mut stats = TreeStats {
+        max_depth: 0,
+        nodes_per_level: Vec::new(),
+        total_nodes: 0,
+        total_files: 0,
+        total_folders: 0,
+        deepest_node: String::new(),
+    };
+    for n in &nodes {
+        stats.add(n, level_of(n));
+    }
+    serde_json::to_string(&stats).map_err(|e| e.to_string())
+}
+
+/// Persist a vault tree. Filesystem vaults get a snapshot in
+/// `<vault>/.focosx/tree.json` (their structure is still read from disk by
+/// `load_tree`); other vaults use the app-managed trees folder.
+#[tauri::command]
+fn save_tree(vault_id: &str, json: String) -> Result<(), String> {
+    if let Some(folder) = find_vault_path(vault_id)?.filter(|p| p.is_absolute()) {
+        let path = vault_folder_tree_file(&folder);
+        if let Some(parent) = path.parent() {
+            ensure_dir(parent)?;
+        }
+        return write_json_file(&path, &json);
+    }
+    write_json_file(&app_tree_file(vault_id)?, &json)
+}
+
+// ----------------- Recently modified -----------------
+
+#[derive(serde::Serialize, Clone)]
+struct RecentFile {
+    file_id: String,
+    name: String,
+    modified_ms: i64,
+}
+
+/// How long (ms) a vault's recently-modified list is served from memory.
+const RECENT_FILES_CACHE_MS: i64 = 60_000;
+
+/// Per-vault cache of files sorted newest-first, with the time (ms since
+/// epoch) the scan started, kept in Tauri managed state.
+#[derive(Default)]
+struct RecentFilesCache(Mutex<HashMap<String, (i64, Vec<RecentFile>)>>);
+
+fn modified_ms(meta: &fs::Metadata) -> Option<i64> {
+    let t = meta.modified().ok()?;
+    let d = t.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(d.as_millis() as i64)
+}
+
+fn collect_recent_files(vault_id: &str) -> Result<Vec<RecentFile>, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(&root, &ScanOptions::default());
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let mut files = Vec::new();
+    flatten_files(nodes, &mut files);
+
+    let mut recent: Vec<RecentFile> = files
+        .into_iter()
+        .filter_map(|n| {
+            let meta = fs::metadata(node_disk_path(&root, &n.id)).ok()?;
+            Some(RecentFile {
+                modified_ms: modified_ms(&meta)?,
+                file_id: n.id,
+                name: n.name,
+            })
+        })
+        .collect();
+    recent.sort_by_key(|f| std::cmp::Reverse(f.modified_ms));
+    Ok(recent)
+}
+
+/// Return the `limit` most recently modified files of a vault, newest first,
+/// as `[{ file_id, name, modified_ms }]`. The full sorted list is cached for
+/// 60s, or until the vault changes (app saves and `watch_vault` events).
+#[tauri::command]
+fn get_recently_modified_nodes(
+    cache: tauri::State<'_, RecentFilesCache>,
+    changes: tauri::State<'_, VaultChangeLog>,
+    vault_id: &str,
+    limit: usize,
+) -> Result<String, String> {
+    let last_change = changes.0.lock().map_err(|e| e.to_string())?.get(vault_id).copied();
+    let now = chrono::Utc::now().timestamp_millis();
+    {
+        let guard = cache.0.lock().map_err(|e| e.to_string())?;
+        if let Some((built_at, files)) = guard.get(vault_id) {
+            let fresh = now - built_at < RECENT_FILES_CACHE_MS && last_change.map(|t| t < *built_at).unwrap_or(true);
+            if fresh {
+                return serde_json::to_string(&files[..limit.min(files.len())]).map_err(|e| e.to_string());
+            }
+        }
+    }
+
+    // Scan without holding the lock so other vaults aren't blocked meanwhile
+    let files = collect_recent_files(vault_id)?;
+    let out = serde_json::to_string(&files[..limit.min(files.len())]).map_err(|e| e.to_string())?;
+    cache.0.lock().map_err(|e| e.to_string())?.insert(vault_id.to_string(), (now, files));
+    Ok(out)
+}
+
+// ----------------- Search -----------------
+
+const DEFAULT_SEARCH_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const SEARCH_SNIPPET_CHARS: usize = 160;
+
+#[derive(serde::Serialize)]
+struct SearchMatch {
+    line: usize,
+    column: usize,
+    snippet: String,
+}
+
+#[derive(serde::Serialize)]
+struct SearchResult {
+    id: String,
+    name: String,
+    matches: Vec<SearchMatch>,
+}
+
+/// Every occurrence of `query` in `content` with its 1-based line/column.
+fn find_matches(content: &str, query: &str, case_sensitive: bool) -> Vec<SearchMatch> {
+    let query = if case_sensitive { query.to_string() } else { query.to_lowercase() };
+    let mut matches = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let haystack = if case_sensitive { line.to_string() } else { line.to_lowercase() };
+        for (byte, _) in haystack.match_indices(&query) {
+            matches.push(SearchMatch {
+                line: idx + 1,
+                column: haystack[..byte].chars().count() + 1,
+                snippet: line.trim().chars().take(SEARCH_SNIPPET_CHARS).collect(),
+            });
+        }
+    }
+    matches
+}
+
+/// Search the text of every file in a vault. Returns
+/// `[{ id, name, matches: [{ line, column, snippet }] }]` for files containing
+/// `query`. Files larger than `max_file_bytes` (default 5 MB) or that aren't
+/// valid UTF-8 are skipped.
+#[tauri::command]
+fn full_text_search_vault(
+    vault_id: &str,
+    query: &str,
+    case_sensitive: bool,
+    max_file_bytes: Option<u64>,
+) -> Result<String, String> {
+    if query.is_empty() {
+        return Ok("[]".to_string());
+    }
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            flat: true,
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let files = nodes.into_iter().filter(is_file_node);
+
+    let limit = max_file_bytes.unwrap_or(DEFAULT_SEARCH_MAX_FILE_BYTES);
+    let results = search_files(&root, files, limit, None, |content| find_matches(content, query, case_sensitive));
+    serde_json::to_string(&results).map_err(|e| e.to_string())
+}
+
+/// Run `find` over the text of each file, skipping files above `limit` bytes
+/// or that aren't valid UTF-8. Stops once `max_results` matches are collected.
+fn search_files(
+    root: &Path,
+    files: impl Iterator<Item = FileSystemNode>,
+    limit: u64,
+    max_results: Option<usize>,
+    find: impl Fn(&str) -> Vec<SearchMatch>,
+) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+    let mut remaining = max_results.unwrap_or(usize::MAX);
+    for f in files {
+        if remaining == 0 {
+            break;
+        }
+        let path = node_disk_path(root, &f.id);
+        if fs::metadata(&path).map(|m| m.len() > limit).unwrap_or(true) {
+            continue;
+        }
+        let content = match fs::read(&path).ok().and_then(|b| String::from_utf8(b).ok()) {
+            Some(c) => c,
+            None => continue,
+        };
+        let mut matches = find(&content);
+        matches.truncate(remaining);
+        if !matches.is_empty() {
+            remaining -= matches.len();
+            results.push(SearchResult {
+                id: f.id,
+                name: f.name,
+                matches,
+            });
+        }
+    }
+    results
+}
+
+/// Every match of `re` in `content`, line by line, with its 1-based line/column.
+fn find_regex_matches(content: &str, re: &regex::Regex) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        for m in re.find_iter(line) {
+            matches.push(SearchMatch {
+                line: idx + 1,
+                column: line[..m.start()].chars().count() + 1,
+                snippet: line.trim().chars().take(SEARCH_SNIPPET_CHARS).collect(),
+            });
+        }
+    }
+    matches
+}
+
+/// Regex variant of `full_text_search_vault` with the same result shape.
+/// Patterns are applied per line. An invalid pattern fails with
+/// `InvalidRegex: ...`; at most `max_results` matches are returned.
+#[tauri::command]
+fn regex_search_vault(
+    vault_id: &str,
+    pattern: &str,
+    case_insensitive: bool,
+    max_results: Option<usize>,
+) -> Result<String, String> {
+    let re = regex::RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| format!("InvalidRegex: {}", e))?;
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            flat: true,
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let files = nodes.into_iter().filter(is_file_node);
+    let results = search_files(&root, files, DEFAULT_SEARCH_MAX_FILE_BYTES, max_results, |content| find_regex_matches(content, &re));
+    serde_json::to_string(&results).map_err(|e| e.to_string())
+}
+
+// ----------------- Duplicates -----------------
+
+const DEFAULT_DUPLICATE_MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(serde::Serialize)]
+struct DuplicateFile {
+    file_id: String,
+    name: String,
+}
+
+#[derive(serde::Serialize)]
+struct DuplicateGroup {
+    sha256: String,
+    size_bytes: u64,
+    files: Vec<DuplicateFile>,
+}
+
+/// Group the files of a vault by identical content. Returns
+/// `[{ sha256, size_bytes, files: [{ file_id, name }] }]` for every group with
+/// two or more members. Files above `max_file_bytes` (default 50 MB) are
+/// skipped; only files sharing a size are hashed.
+#[tauri::command]
+fn detect_duplicate_files(vault_id: &str, max_file_bytes: Option<u64>) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            include_metadata: true,
+            flat: true,
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let files = nodes.into_iter().filter(is_file_node);
+
+    let limit = max_file_bytes.unwrap_or(DEFAULT_DUPLICATE_MAX_FILE_BYTES);
+    let mut by_size: HashMap<u64, Vec<FileSystemNode>> = HashMap::new();
+    for f in files {
+        match f.size_bytes {
+            Some(size) if size <= limit => by_size.entry(size).or_default().push(f),
+            _ => {}
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (size, same_size) in by_size.into_iter().filter(|(_, v)| v.len() > 1) {
+        let mut by_hash: HashMap<String, Vec<DuplicateFile>> = HashMap::new();
+        for f in same_size {
+            let hash = match sha256_file(&node_disk_path(&root, &f.id)) {
+                Ok(h) => h,
+                Err(_) => continue,
+            };
+            by_hash.entry(hash).or_default().push(DuplicateFile { file_id: f.id, name: f.name });
+        }
+        for (sha256, mut members) in by_hash.into_iter().filter(|(_, v)| v.len() > 1) {
+            members.sort_by(|a, b| a.file_id.cmp(&b.file_id));
+            groups.push(DuplicateGroup { sha256, size_bytes: size, files: members });
+        }
+    }
+    groups.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then_with(|| a.files[0].file_id.cmp(&b.files[0].file_id)));
+    serde_json::to_string(&groups).map_err(|e| e.to_string())
+}
+
+// ----------------- Export -----------------
+
+/// Every file below `dir` (recursively), for archiving metadata folders.
+fn collect_files_recursive(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files_recursive(&path, out);
+        } else {
+            out.push(path);
+        }
+    }
+}
+
+/// Package a vault into a ZIP archive at `dest_path` (absolute, inside the
+/// app data folder or a vault), keeping the folder structure. Exclusion patterns from the vault config apply; the `.focosx`
+/// folder is only added with `include_metadata`. Returns
+/// `{ fileCount, totalBytes, archivePath }`.
+#[tauri::command]
+fn export_vault_as_zip(vault_id: &str, dest_path: String, include_metadata: Option<bool>) -> Result<String, String> {
+    use std::io::Write;
+
+    let root = vault_root(vault_id)?;
+    let dest = PathBuf::from(&dest_path);
+    if !dest.is_absolute() {
+        return Err(format!("expected an absolute path, got {}", dest_path));
+    }
+    check_command_path(&dest)?;
+    let opts = configured_scan_options(&root, &ScanOptions::default());
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let mut nodes_flat = Vec::new();
+    flatten_files(nodes, &mut nodes_flat);
+    let mut paths: Vec<PathBuf> = nodes_flat.iter().map(|n| node_disk_path(&root, &n.id)).collect();
+    if include_metadata.unwrap_or(false) {
+        collect_files_recursive(&root.join(".focosx"), &mut paths);
+    }
+
+    if let Some(parent) = dest.parent() {
+        ensure_dir(parent)?;
+    }
+    let file = fs::File::create(&dest).map_err(|e| format!("failed to create {}: {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let mut file_count = 0u64;
+    let mut total_bytes = 0u64;
+    for path in paths {
+        // the archive itself may live inside the vault
+        if path == dest {
+            continue;
+        }
+        let rel = match path.strip_prefix(&root) {
+            Ok(r) => r.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .large_file(size >= u32::MAX as u64);
+        let mut src = fs::File::open(&path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        zip.start_file(rel, options).map_err(|e| e.to_string())?;
+        std::io::copy(&mut src, &mut zip).map_err(|e| format!("failed to archive {}: {}", path.display(), e))?;
+        file_count += 1;
+        total_bytes += size;
+    }
+    zip.finish().map_err(|e| e.to_string())?.flush().map_err(|e| e.to_string())?;
+
+    let out = json!({
+        "fileCount": file_count,
+        "totalBytes": total_bytes,
+        "archivePath": dest.to_string_lossy(),
+    });
+    Ok(out.to_string())
+}
+
+// ----------------- Vault README -----------------
+
+/// Count notes (`.md` files) and folders in a scanned tree. `skip_id` is left out.
+fn count_notes_and_folders(nodes: &[FileSystemNode], skip_id: &str) -> (usize, usize) {
+    let mut notes = 0;
+    let mut folders = 0;
+    for n in nodes {
+        if n.node_type == "FOLDER" {
+            folders += 1;
+        } else if n.name.ends_with(".md") && n.id != skip_id {
+            notes += 1;
+        }
+        if let Some(children) = &n.children {
+            let (cn, cf) = count_notes_and_folders(children, skip_id);
+            notes += cn;
+            folders += cf;
+        }
+    }
+    (notes, folders)
+}
+
+/// Build the README body for `generate_vault_readme`.
+fn render_vault_readme(name: &str, vault_id: &str, nodes: &[FileSystemNode]) -> String {
+    let readme_id = format!("{}:README.md", vault_id);
+    let (note_count, folder_count) = count_notes_and_folders(nodes, &readme_id);
+    let mut out = format!("# {}\n\n", name);
+    out.push_str(&format!("{} notes in {} folders.\n", note_count, folder_count));
+
+    let folders: Vec<&FileSystemNode> = nodes.iter().filter(|n| n.node_type == "FOLDER").collect();
+    if !folders.is_empty() {
+        out.push_str("\n## Folders\n\n");
+        for f in folders {
+            out.push_str(&format!("- {}\n", f.name));
+            for sub in f.children.iter().flatten().filter(|n| n.node_type == "FOLDER") {
+                out.push_str(&format!("  - {}\n", sub.name));
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    flatten_files(nodes.to_vec(), &mut files);
+    files.retain(|f| f.name.ends_with(".md") && f.id != readme_id);
+    files.sort_by_key(|f| std::cmp::Reverse(f.modified_ms));
+    if !files.is_empty() {
+        out.push_str("\n## Recently modified\n\n");
+        for f in files.iter().take(10) {
+            let rel = f.id.split_once(':').map(|(_, r)| r).unwrap_or(&f.id);
+            let date = f
+                .modified_ms
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|d| format!(" ({})", d.format("%Y-%m-%d")))
+                .unwrap_or_default();
+            out.push_str(&format!("- [{}](<{}>){}\n", f.name.trim_end_matches(".md"), rel, date));
+        }
+    }
+    out
+}
+
+/// Write (or refresh) `<vault>/README.md` with the vault name, note and folder
+/// counts, the top two folder levels and the 10 most recently modified notes.
+#[tauri::command]
+fn generate_vault_readme(vault_id: &str) -> Result<(), String> {
+    let root = vault_root(vault_id)?;
+    let name = read_vaults()?
+        .iter()
+        .find(|v| v.get("id").and_then(|x| x.as_str()) == Some(vault_id))
+        .and_then(|v| v.get("name").and_then(|x| x.as_str()).map(|s| s.to_string()))
+        .unwrap_or_else(|| vault_id.to_string());
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            include_metadata: true,
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    write_text_file(&root.join("README.md"), &render_vault_readme(&name, vault_id, &nodes)).map(|_| ())
+}
+
+// ----------------- Canvases -----------------
+
+#[derive(serde::Serialize)]
+struct CanvasFile {
+    file_id: String,
+    name: String,
+    relative_path: String,
+    modified_ms: Option<i64>,
+}
+
+/// List every `.canvas` file in a vault as `[{ file_id, name, relative_path, modified_ms }]`
+/// without reading their content. Cheap enough to call on every vault switch.
+#[tauri::command]
+fn list_canvas_files(vault_id: &str) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            filter_extensions: Some(vec!["canvas".to_string()]),
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let mut files = Vec::new();
+    flatten_files(nodes, &mut files);
+
+    let canvases: Vec<CanvasFile> = files
+        .into_iter()
+        .filter(|n| n.node_type == "CANVAS")
+        .map(|n| {
+            let path = node_disk_path(&root, &n.id);
+            CanvasFile {
+                relative_path: n.id.split_once(':').map(|(_, r)| r).unwrap_or(&n.id).to_string(),
+                modified_ms: fs::metadata(&path).ok().as_ref().and_then(modified_ms),
+                file_id: n.id,
+                name: n.name,
+            }
+        })
+        .collect();
+    serde_json::to_string(&canvases).map_err(|e| e.to_string())
+}
+
+// ----------------- File Contents -----------------
+
+/// Resolve a file ID to the on-disk path used for its content.
+/// - `vaultId:relative/path` resolves inside the vault folder.
+/// - Legacy IDs resolve to the owning vault's `.focosx/contents/<fileId>.json`,
+///   or to the app-managed `contents/<fileId>.json`; IDs containing path
+///   separators or `..` are rejected.
+fn resolve_file_path(file_id: &str) -> Result<PathBuf, String> {
+    // Check if file_id contains vault prefix (vaultId:path)
+    if let Some((vault_id, path)) = file_id.split_once(':') {
+        if let Some(mut file_path) = find_vault_path(vault_id)? {
+            file_path.push(path);
+            return Ok(file_path);
+        }
+    }
+
+    // Legacy/Fallback logic: the ID becomes a file name, so anything that
+    // could step out of the contents folder is refused.
+    if file_id.is_empty() || file_id.contains(['/', '\\']) || file_id.contains("..") {
+        return Err(format!("PathEscapeAttempt: invalid file ID {}", file_id));
+    }
+    // If the file is part of a vault folder on disk, its content lives in that
+    // vault's `.focosx/contents/<fileId>.json` so user files and metadata live together.
+    if let Ok(Some(vpath)) = find_vault_folder_for_file(file_id) {
+        let mut content_path = vpath;
+        content_path.push(".focosx");
+        content_path.push("contents");
+        content_path.push(format!("{}.json", file_id));
+        return Ok(content_path);
+    }
+
+    let mut base = base_dir()?;
+    base.push("contents");
+    base.push(format!("{}.json", file_id));
+    Ok(base)
+}
+
+/// `resolve_file_path`, rejecting IDs like `vaultId:../../etc/passwd` that
+/// resolve outside their vault folder.
+fn resolve_file_path_checked(file_id: &str) -> Result<PathBuf, String> {
+    let path = resolve_file_path(file_id)?;
+    if let Some((vault_id, _)) = file_id.split_once(':') {
+        if let Some(root) = find_vault_path(vault_id)? {
+            ensure_path_within(&root, &path)?;
+        }
+    }
+    Ok(path)
+}
+
+const DEFAULT_FILE_READ_TIMEOUT_MS: u64 = 5000;
+
+/// Load a file's content. The read is abandoned with a `ReadTimeout` error
+/// after `timeout_ms`, falling back to the `file_read_timeout_ms` preference
+/// (default 5000ms).
+#[tauri::command]
+fn load_file_content(file_id: &str, timeout_ms: Option<u64>) -> Result<String, String> {
+    let timeout_ms = match timeout_ms {
+        Some(ms) => ms,
+        None => read_preferences()?
+            .get("file_read_timeout_ms")
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_FILE_READ_TIMEOUT_MS),
+    };
+    // read_text_file tolerates a missing file (returns an empty string)
+    read_text_file_with_timeout(&resolve_file_path_checked(file_id)?, std::time::Duration::from_millis(timeout_ms))
+}
+
+/// Hex SHA-256 of a file's bytes, or an empty string when the file doesn't
+/// exist (matching `load_file_content`).
+#[tauri::command]
+fn file_content_hash(file_id: &str) -> Result<String, String> {
+    let path = resolve_file_path_checked(file_id)?;
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    sha256_file(&path)
+}
+
+#[tauri::command]
+fn save_file_content(app: tauri::AppHandle, file_id: &str, json: String) -> Result<(), String> {
+    let path = resolve_file_path(file_id)?;
+    // Reject IDs like `vaultId:../../etc/passwd` that would write outside the vault
+    if let Some((vault_id, _)) = file_id.split_once(':') {
+        if let Some(root) = find_vault_path(vault_id)? {
+            ensure_path_within(&root, &path)?;
+            // Warn (but still write) when the vault is nearly at its configured quota
+            if let Some(usage) = storage_usage_if_limited(vault_id, &root)? {
+                if usage.percent_used.unwrap_or(0.0) > STORAGE_WARNING_PERCENT {
+                    let _ = app.emit("storage-warning", json!({ "vault_id": vault_id, "usage": usage }));
+                }
+            }
+        }
+    }
+    if let Err(e) = snapshot_file_version(file_id, &path, &json) {
+        eprintln!("[save_file_content] failed to snapshot {}: {}", file_id, e);
+    }
+    if write_text_file(&path, &json)? == WriteOutcome::Written {
+        if let Some((vault_id, _)) = file_id.split_once(':') {
+            note_vault_change(&app, vault_id);
+        }
+    }
+    Ok(())
+}
+
+/// Byte range of the first occurrence of `needle` in `hay` at or after
+/// `from`. Case-insensitive matching compares char by char, so offsets stay
+/// valid even where lowercasing changes a character's byte length.
+fn find_text(hay: &str, needle: &str, from: usize, case_sensitive: bool) -> Option<(usize, usize)> {
+    if case_sensitive {
+        return hay[from..].find(needle).map(|i| (from + i, from + i + needle.len()));
+    }
+    for (i, _) in hay[from..].char_indices() {
+        let start = from + i;
+        let mut rest = hay[start..].char_indices();
+        let mut end = start;
+        let matched = needle.chars().all(|n| match rest.next() {
+            Some((j, h)) if h.to_lowercase().eq(n.to_lowercase()) => {
+                end = start + j + h.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+/// Replace the first (or every) occurrence of `search` and return the new
+/// text with the number of replacements.
+fn replace_text(content: &str, search: &str, replacement: &str, all: bool, case_sensitive: bool) -> (String, usize) {
+    let mut out = String::with_capacity(content.len());
+    let mut count = 0;
+    let mut pos = 0;
+    while let Some((start, end)) = find_text(content, search, pos, case_sensitive) {
+        out.push_str(&content[pos..start]);
+        out.push_str(replacement);
+        pos = end;
+        count += 1;
+        if !all {
+            break;
+        }
+    }
+    out.push_str(&content[pos..]);
+    (out, count)
+}
+
+/// Find and replace inside a file without sending its content over IPC.
+/// The result is written through `save_file_content` (atomic, versioned).
+/// Returns `{ replacedCount }`; nothing is written when there is no match.
+#[tauri::command]
+fn replace_in_file(
+    app: tauri::AppHandle,
+    file_id: &str,
+    search: &str,
+    replacement: &str,
+    all_occurrences: bool,
+    case_sensitive: bool,
+) -> Result<String, String> {
+    if search.is_empty() {
+        return Err("search text must not be empty".to_string());
+    }
+    let path = resolve_file_path_checked(file_id)?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("read error {}: {}", path.display(), e))?;
+    let (updated, count) = replace_text(&content, search, replacement, all_occurrences, case_sensitive);
+    if count > 0 {
+        save_file_content(app, file_id, updated)?;
+    }
+    Ok(json!({ "replacedCount": count }).to_string())
+}
+
+/// Return a plain-text snippet of a file for hover previews: only the first
+/// `max_chars` bytes are read, frontmatter and the Markdown characters
+/// `# * _ [ ] `` ` are stripped. A missing file yields an empty string.
+#[tauri::command]
+fn get_file_preview(file_id: &str, max_chars: usize) -> Result<String, String> {
+    use std::io::Read;
+    let path = resolve_file_path_checked(file_id)?;
+    let file = match fs::File::open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(e) => return Err(format!("read error {}: {}", path.display(), e)),
+    };
+    let mut buf = Vec::new();
+    file.take(max_chars as u64)
+        .read_to_end(&mut buf)
+        .map_err(|e| format!("read error {}: {}", path.display(), e))?;
+    // the byte limit may cut a multi-byte character in half
+    let text = match std::str::from_utf8(&buf) {
+        Ok(t) => t,
+        Err(e) => std::str::from_utf8(&buf[..e.valid_up_to()]).unwrap_or_default(),
+    };
+
+    let body = if text.starts_with("---\n") || text.starts_with("---\r\n") {
+        // frontmatter that doesn't close within the preview leaves nothing to show
+        match split_frontmatter(text) {
+            (Some(_), body) => body,
+            (None, _) => "",
+        }
+    } else {
+        text
+    };
+    let mut preview = String::with_capacity(body.len());
+    preview.extend(body.trim_start().chars().filter(|c| !matches!(c, '#' | '*' | '_' | '[' | ']' | '`')));
+    Ok(preview)
+}
+
+// ----------------- File versions -----------------
+
+const DEFAULT_MAX_FILE_VERSIONS: usize = 20;
+
+/// Snapshot folder of a file and the number of snapshots to keep. Snapshots
+/// live in `<vault>/.focosx/versions/<sha256(fileId)>/` for filesystem vaults
+/// and `versions/<sha256(fileId)>/` in the app data dir otherwise; hashing
+/// keeps IDs with `:` or `/` out of the folder name.
+fn file_versions_dir(file_id: &str) -> Result<(PathBuf, usize), String> {
+    use sha2::{Digest, Sha256};
+    let mut vault_folder = None;
+    let mut config = VaultConfig::default();
+    if let Some((vault_id, _)) = file_id.split_once(':') {
+        match find_vault_path(vault_id)?.filter(|p| p.is_absolute()) {
+            Some(root) => vault_folder = Some(root),
+            None => config = read_vault_config_file(&vault_config_file(vault_id)?),
+        }
+    } else if let Ok(Some(vpath)) = find_vault_folder_for_file(file_id) {
+        vault_folder = Some(vpath);
+    }
+    let mut dir = match vault_folder {
+        Some(root) => {
+            config = read_vault_config(&root);
+            root.join(".focosx")
+        }
+        None => base_dir()?,
+    };
+    dir.push("versions");
+    dir.push(format!("{:x}", Sha256::digest(file_id.as_bytes())));
+    Ok((dir, config.max_file_versions.unwrap_or(DEFAULT_MAX_FILE_VERSIONS)))
+}
+
+/// Snapshot timestamps of a file, oldest first.
+fn list_file_versions(dir: &Path) -> Vec<i64> {
+    let mut out: Vec<i64> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.strip_suffix(".json")?.parse().ok())
+        .collect();
+    out.sort_unstable();
+    out
+}
+
+/// Keep the current content of `path` as a snapshot before it is replaced
+/// by `new_content`, then drop the oldest snapshots over the vault's limit.
+fn snapshot_file_version(file_id: &str, path: &Path, new_content: &str) -> Result<(), String> {
+    let (dir, max) = file_versions_dir(file_id)?;
+    if max == 0 {
+        return Ok(());
+    }
+    let current = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return Ok(()),
+    };
+    if current == new_content {
+        return Ok(());
+    }
+    let now = chrono::Utc::now().timestamp_millis();
+    write_text_file(&dir.join(format!("{}.json", now)), &current)?;
+
+    let versions = list_file_versions(&dir);
+    for ts in versions.iter().take(versions.len().saturating_sub(max)) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", ts)));
+    }
+    Ok(())
+}
+
+/// Saved snapshots of a file, newest first, as `[{ timestamp, sizeBytes }]`.
+#[tauri::command]
+fn get_file_versions(file_id: &str) -> Result<String, String> {
+    let (dir, _) = file_versions_dir(file_id)?;
+    let out: Vec<serde_json::Value> = list_file_versions(&dir)
+        .into_iter()
+        .rev()
+        .map(|ts| {
+            let size = fs::metadata(dir.join(format!("{}.json", ts))).map(|m| m.len()).unwrap_or(0);
+            json!({ "timestamp": ts, "sizeBytes": size })
+        })
+        .collect();
+    serde_json::to_string(&out).map_err(|e| e.to_string())
+}
+
+/// Text of the snapshot taken at `timestamp`.
+#[tauri::command]
+fn get_file_version_content(file_id: &str, timestamp: i64) -> Result<String, String> {
+    let (dir, _) = file_versions_dir(file_id)?;
+    let path = dir.join(format!("{}.json", timestamp));
+    fs::read_to_string(&path).map_err(|e| format!("version {} of {} not found: {}", timestamp, file_id, e))
+}
+
+/// Make the snapshot taken at `timestamp` the current content. The content
+/// being replaced is snapshotted too, so a restore can itself be undone.
+#[tauri::command]
+fn restore_file_version(app: tauri::AppHandle, file_id: &str, timestamp: i64) -> Result<(), String> {
+    let content = get_file_version_content(file_id, timestamp)?;
+    save_file_content(app, file_id, content)
+}
+
+// ----------------- Storage quota -----------------
+
+/// `save_file_content` emits `storage-warning` above this share of the limit.
+const STORAGE_WARNING_PERCENT: f64 = 95.0;
+
+#[derive(serde::Serialize, Clone)]
+struct StorageUsage {
+    used_bytes: u64,
+    limit_bytes: Option<u64>,
+    percent_used: Option<f64>,
+}
+
+/// Total size of all files below `path` (symlinks are not followed).
+fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            match entry.metadata() {
+                Ok(m) if m.is_dir() => total += dir_size(&entry.path()),
+                Ok(m) => total += m.len(),
+                Err(_) => {}
+            }
+        }
+    }
+    total
+}
+
+/// Load vault_storage_limits.json (vault_id -> limit in bytes).
+fn read_storage_limits() -> Result<HashMap<String, u64>, String> {
+    let mut base = base_dir()?;
+    base.push("vault_storage_limits.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+fn storage_usage(root: &Path, limit_bytes: Option<u64>) -> StorageUsage {
+    let used_bytes = dir_size(root);
+    StorageUsage {
+        used_bytes,
+        limit_bytes,
+        percent_used: limit_bytes.filter(|l| *l > 0).map(|l| used_bytes as f64 / l as f64 * 100.0),
+    }
+}
+
+/// Usage for a vault that has a configured limit; `None` (without walking the
+/// vault) when no limit is set.
+fn storage_usage_if_limited(vault_id: &str, root: &Path) -> Result<Option<StorageUsage>, String> {
+    Ok(read_storage_limits()?
+        .get(vault_id)
+        .map(|limit| storage_usage(root, Some(*limit))))
+}
+
+/// Configure a storage limit (bytes) for a vault. A limit of 0 removes it.
+#[tauri::command]
+fn set_vault_storage_limit(vault_id: &str, limit_bytes: u64) -> Result<(), String> {
+    let mut limits = read_storage_limits()?;
+    if limit_bytes == 0 {
+        limits.remove(vault_id);
+    } else {
+        limits.insert(vault_id.to_string(), limit_bytes);
+    }
+    let mut base = base_dir()?;
+    base.push("vault_storage_limits.json");
+    let s = serde_json::to_string_pretty(&limits).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)
+}
+
+/// Return `{ used_bytes, limit_bytes, percent_used }` for a filesystem vault.
+#[tauri::command]
+fn get_storage_quota_usage(vault_id: &str) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let limit = read_storage_limits()?.get(vault_id).copied();
+    serde_json::to_string(&storage_usage(&root, limit)).map_err(|e| e.to_string())
+}
+
+// ----------------- Recently opened -----------------
+
+const DEFAULT_RECENTLY_OPENED_MAX: usize = 50;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecentlyOpened {
+    vault_id: String,
+    file_id: String,
+    file_name: String,
+    opened_at_ms: i64,
+}
+
+fn recently_opened_path() -> Result<PathBuf, String> {
+    Ok(base_dir()?.join("recently_opened.json"))
+}
+
+fn read_recently_opened() -> Result<Vec<RecentlyOpened>, String> {
+    let raw = read_json_file(&recently_opened_path()?)?;
+    if raw.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+/// Move (or add) a file to the front of the recently opened list. The list is
+/// capped by the `recently_opened_max` preference (default 50).
+#[tauri::command]
+fn record_recently_opened(vault_id: &str, file_id: &str, file_name: &str) -> Result<(), String> {
+    let max = read_preferences()?
+        .get("recently_opened_max")
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_RECENTLY_OPENED_MAX);
+    let mut list = read_recently_opened()?;
+    list.retain(|e| !(e.vault_id == vault_id && e.file_id == file_id));
+    list.insert(
+        0,
+        RecentlyOpened {
+            vault_id: vault_id.to_string(),
+            file_id: file_id.to_string(),
+            file_name: file_name.to_string(),
+            opened_at_ms: chrono::Utc::now().timestamp_millis(),
+        },
+    );
+    list.truncate(max);
+    let s = serde_json::to_string_pretty(&list).map_err(|e| e.to_string())?;
+    write_json_file(&recently_opened_path()?, &s)
+}
+
+/// Recently opened files, most recent first, as
+/// `[{ vaultId, fileId, fileName, openedAtMs }]`.
+#[tauri::command]
+fn get_recently_opened() -> Result<String, String> {
+    serde_json::to_string(&read_recently_opened()?).map_err(|e| e.to_string())
+}
+
+// ----------------- Bookmarks -----------------
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Bookmark {
+    vault_id: String,
+    file_id: String,
+    file_name: String,
+    added_at_ms: i64,
+}
+
+fn bookmarks_path() -> Result<PathBuf, String> {
+    Ok(base_dir()?.join("bookmarks.json"))
+}
+
+/// Add (`bookmarked: true`) or remove a bookmark. Re-bookmarking an existing
+/// entry keeps its original `addedAtMs`.
+#[tauri::command]
+fn set_bookmarked_node(vault_id: &str, file_id: &str, file_name: &str, bookmarked: bool) -> Result<(), String> {
+    let path = bookmarks_path()?;
+    let raw = read_json_file(&path)?;
+    let mut list: Vec<Bookmark> = if raw.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_json::from_str(&raw).map_err(|e| e.to_string())?
+    };
+    let existing = list.iter().position(|b| b.vault_id == vault_id && b.file_id == file_id);
+    match (existing, bookmarked) {
+        (None, true) => list.push(Bookmark {
+            vault_id: vault_id.to_string(),
+            file_id: file_id.to_string(),
+            file_name: file_name.to_string(),
+            added_at_ms: chrono::Utc::now().timestamp_millis(),
+        }),
+        (Some(i), true) => list[i].file_name = file_name.to_string(),
+        (Some(i), false) => {
+            list.remove(i);
+        }
+        (None, false) => return Ok(()),
+    }
+    let s = serde_json::to_string_pretty(&list).map_err(|e| e.to_string())?;
+    write_json_file(&path, &s)
+}
+
+/// All bookmarks, oldest first, as `[{ vaultId, fileId, fileName, addedAtMs }]`.
+#[tauri::command]
+fn get_bookmarked_nodes() -> Result<String, String> {
+    let raw = read_json_file(&bookmarks_path()?)?;
+    if raw.trim().is_empty() {
+        return Ok("[]".to_string());
+    }
+    Ok(raw)
+}
+
+// ----------------- Sync manifest -----------------
+
+const SYNC_MANIFEST_CACHE_MS: i64 = 30_000;
+
+/// Time (ms since epoch) of the last change made to each vault through the
+/// app. Cached per-vault data older than this entry is stale.
+#[derive(Default)]
+struct VaultChangeLog(Mutex<HashMap<String, i64>>);
+
+/// Record that `vault_id` just changed.
+fn note_vault_change(app: &tauri::AppHandle, vault_id: &str) {
+    if let Ok(mut log) = app.state::<VaultChangeLog>().0.lock() {
+        log.insert(vault_id.to_string(), chrono::Utc::now().timestamp_millis());
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SyncManifestEntry {
+    file_id: String,
+    relative_path: String,
+    sha256: String,
+    size_bytes: u64,
+    modified_ms: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SyncManifest {
+    generated_at_ms: i64,
+    files: Vec<SyncManifestEntry>,
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let mut file = fs::File::open(path).map_err(|e| format!("read error {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("read error {}: {}", path.display(), e))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn build_sync_manifest(vault_id: &str) -> Result<SyncManifest, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            include_metadata: true,
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let mut files = Vec::new();
+    flatten_files(nodes, &mut files);
+
+    let mut entries = Vec::with_capacity(files.len());
+    for f in files {
+        // files that vanished or became unreadable since the scan are left out
+        let Ok(sha256) = sha256_file(&node_disk_path(&root, &f.id)) else {
+            continue;
+        };
+        entries.push(SyncManifestEntry {
+            sha256,
+            relative_path: f.id.split_once(':').map(|(_, r)| r).unwrap_or(&f.id).to_string(),
+            size_bytes: f.size_bytes.unwrap_or(0),
+            modified_ms: f.modified_ms.unwrap_or(0).max(0) as u64,
+            file_id: f.id,
+        });
+    }
+    Ok(SyncManifest {
+        generated_at_ms: chrono::Utc::now().timestamp_millis(),
+        files: entries,
+    })
+}
+
+/// Return `{ generated_at_ms, files: [{ file_id, relative_path, sha256, size_bytes, modified_ms }] }`
+/// for every readable file of a vault. The manifest is stored in `sync_manifests/<vaultId>.json`
+/// and reused for 30s unless the vault changed in the meantime.
+#[tauri::command]
+fn get_vault_sync_manifest(changes: tauri::State<'_, VaultChangeLog>, vault_id: &str) -> Result<String, String> {
+    let mut path = base_dir()?;
+    path.push("sync_manifests");
+    ensure_dir(&path)?;
+    path.push(format!("{}.json", vault_id));
+
+    let last_change = changes.0.lock().map_err(|e| e.to_string())?.get(vault_id).copied();
+    let now = chrono::Utc::now().timestamp_millis();
+    if let Ok(cached) = serde_json::from_str::<SyncManifest>(&read_json_file(&path)?) {
+        let fresh = now - cached.generated_at_ms < SYNC_MANIFEST_CACHE_MS
+            && last_change.map(|t| t <= cached.generated_at_ms).unwrap_or(true);
+        if fresh {
+            return serde_json::to_string(&cached).map_err(|e| e.to_string());
+        }
+    }
+
+    let manifest = build_sync_manifest(vault_id)?;
+    let out = serde_json::to_string(&manifest).map_err(|e| e.to_string())?;
+    write_json_file(&path, &out)?;
+    Ok(out)
+}
+
+// ----------------- Note frontmatter -----------------
+
+/// Split a Markdown document into its YAML frontmatter body (between the
+/// leading `---` fences) and the remaining text. Returns `None` for the
+/// frontmatter when the document doesn't start with a fence.
+fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let rest = match content.strip_prefix("---\r\n").or_else(|| content.strip_prefix("---\n")) {
+        Some(r) => r,
+        None => return (None, content),
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Strip matching single or double quotes around a YAML scalar.
+fn unquote_yaml(v: &str) -> String {
+    let v = v.trim();
+    if v.len() >= 2 && ((v.starts_with('"') && v.ends_with('"')) || (v.starts_with('\'') && v.ends_with('\''))) {
+        v[1..v.len() - 1].to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+/// Read a list-valued key from frontmatter. Supports both the inline
+/// `key: [a, b]` form and the block form (`key:` followed by `- a` lines).
+fn frontmatter_list(frontmatter: &str, key: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut lines = frontmatter.lines().peekable();
+    while let Some(line) = lines.next() {
+        let value = match line.strip_prefix(key).and_then(|r| r.strip_prefix(':')) {
+            Some(v) => v.trim(),
+            None => continue,
+        };
+        if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            out.extend(inner.split(',').map(unquote_yaml).filter(|s| !s.is_empty()));
+        } else if !value.is_empty() {
+            out.push(unquote_yaml(value));
+        } else {
+            while let Some(item) = lines.peek().and_then(|l| l.trim_start().strip_prefix("- ")) {
+                out.push(unquote_yaml(item));
+                lines.next();
+            }
+        }
+        break;
+    }
+    out
+}
+
+/// Replace (or remove, when `items` is empty) a list-valued key in the
+/// frontmatter, keeping every other line untouched.
+fn set_frontmatter_list(frontmatter: &str, key: &str, items: &[String]) -> String {
+    let mut out = String::new();
+    let mut lines = frontmatter.lines().peekable();
+    while let Some(line) = lines.next() {
+        if line.strip_prefix(key).map(|r| r.starts_with(':')).unwrap_or(false) {
+            // drop the key and any block items belonging to it
+            while lines.peek().map(|l| l.trim_start().starts_with("- ")).unwrap_or(false) {
+                lines.next();
+            }
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if !items.is_empty() {
+        out.push_str(key);
+        out.push_str(":\n");
+        for item in items {
+            out.push_str(&format!("  - {}\n", serde_json::to_string(item).unwrap_or_default()));
+        }
+    }
+    out
+}
+
+/// Return the `aliases` listed in a note's frontmatter (empty if none).
+#[tauri::command]
+fn get_note_aliases(file_id: &str) -> Result<Vec<String>, String> {
+    let content = read_text_file(&resolve_file_path_checked(file_id)?)?;
+    match split_frontmatter(&content).0 {
+        Some(fm) => Ok(frontmatter_list(fm, "aliases")),
+        None => Ok(vec![]),
+    }
+}
+
+/// Replace the `aliases` list in a note's frontmatter, creating the
+/// frontmatter block when the note has none.
+#[tauri::command]
+fn set_note_aliases(file_id: &str, aliases: Vec<String>) -> Result<(), String> {
+    let path = resolve_file_path_checked(file_id)?;
+    let content = read_text_file(&path)?;
+    let (fm, body) = split_frontmatter(&content);
+    let fm = set_frontmatter_list(fm.unwrap_or(""), "aliases", &aliases);
+    let updated = if fm.is_empty() {
+        body.to_string()
+    } else {
+        format!("---\n{}---\n{}", fm, body)
+    };
+    write_text_file(&path, &updated).map(|_| ())
+}
+
+/// Parse a note's YAML frontmatter into a JSON object. Returns `null` when
+/// the note has no frontmatter and an `InvalidFrontmatter` error when the
+/// YAML doesn't parse.
+#[tauri::command]
+fn extract_frontmatter(file_id: &str) -> Result<String, String> {
+    let content = read_text_file(&resolve_file_path_checked(file_id)?)?;
+    let fm = match split_frontmatter(&content).0 {
+        Some(fm) => fm,
+        None => return Ok("null".to_string()),
+    };
+    let value: serde_json::Value = serde_yaml::from_str(fm).map_err(|e| format!("InvalidFrontmatter: {}", e))?;
+    let value = match value {
+        // an empty `---\n---` block
+        serde_json::Value::Null => json!({}),
+        v => v,
+    };
+    Ok(value.to_string())
+}
+
+// ----------------- Text analysis -----------------
+
+/// Reduce a Markdown note to plain text: drops frontmatter, fenced code
+/// blocks, link URLs and the syntax characters `# * _ [ ] ` > ~ |`.
+fn markdown_plain_text(markdown: &str) -> String {
+    let (_, body) = split_frontmatter(markdown);
+    let mut out = String::with_capacity(body.len());
+    let mut in_fence = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        // [text](url) -> [text]
+        let mut rest = line;
+        let mut kept = String::with_capacity(line.len());
+        while let Some(pos) = rest.find("](") {
+            match rest[pos..].find(')') {
+                Some(end) => {
+                    kept.push_str(&rest[..=pos]);
+                    rest = &rest[pos + end + 1..];
+                }
+                None => break,
+            }
+        }
+        kept.push_str(rest);
+        out.extend(kept.chars().filter(|c| !matches!(c, '#' | '*' | '_' | '[' | ']' | '`' | '>' | '~' | '|')));
+        out.push('\n');
+    }
+    out
+}
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "am", "an", "and", "any", "are", "as", "at", "be",
+    "because", "been", "before", "being", "below", "between", "both", "but", "by", "can", "could", "did", "do",
+    "does", "doing", "down", "during", "each", "few", "for", "from", "further", "had", "has", "have", "having",
+    "he", "her", "here", "hers", "herself", "him", "himself", "his", "how", "i", "if", "in", "into", "is", "it",
+    "its", "itself", "just", "me", "more", "most", "my", "myself", "no", "nor", "not", "now", "of", "off", "on",
+    "once", "only", "or", "other", "our", "ours", "ourselves", "out", "over", "own", "same", "she", "should", "so",
+    "some", "such", "than", "that", "the", "their", "theirs", "them", "themselves", "then", "there", "these",
+    "they", "this", "those", "through", "to", "too", "under", "until", "up", "very", "was", "we", "were", "what",
+    "when", "where", "which", "while", "who", "whom", "why", "will", "with", "would", "you", "your", "yours",
+    "yourself", "yourselves",
+];
+
+#[derive(serde::Serialize)]
+struct WordCount {
+    word: String,
+    count: usize,
+}
+
+/// Return the `top_n` most frequent non-stop-words of a note as
+/// `[{ word, count }]`, most frequent first (ties alphabetical).
+#[tauri::command]
+fn get_note_word_frequency(file_id: &str, top_n: usize) -> Result<String, String> {
+    let content = read_text_file(&resolve_file_path_checked(file_id)?)?;
+    let text = markdown_plain_text(&content).to_lowercase();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for token in text.split(|c: char| !c.is_alphanumeric() && c != '\'') {
+        let token = token.trim_matches('\'');
+        if token.chars().count() < 2 || token.chars().all(|c| c.is_numeric()) || STOP_WORDS.contains(&token) {
+            continue;
+        }
+        *counts.entry(token).or_insert(0) += 1;
+    }
+
+    let mut words: Vec<WordCount> = counts
+        .into_iter()
+        .map(|(w, count)| WordCount { word: w.to_string(), count })
+        .collect();
+    words.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+    words.truncate(top_n);
+    serde_json::to_string(&words).map_err(|e| e.to_string())
+}
+
+/// Word / sentence / paragraph counts of plain text. Sentences end at `.`,
+/// `!` or `?` followed by whitespace (or the end of the text); trailing text
+/// without a terminator still counts as a sentence.
+fn count_text(text: &str) -> (u64, u64, u64) {
+    let words = text.split_whitespace().filter(|w| w.chars().any(|c| c.is_alphanumeric())).count() as u64;
+
+    let mut sentences = 0;
+    let mut pending = false; // words seen since the last sentence end
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() {
+            pending = true;
+        } else if matches!(c, '.' | '!' | '?') && pending && chars.peek().map(|n| n.is_whitespace()).unwrap_or(true) {
+            sentences += 1;
+            pending = false;
+        }
+    }
+    if pending {
+        sentences += 1;
+    }
+
+    let mut paragraphs = 0;
+    let mut in_paragraph = false;
+    for line in text.lines() {
+        let blank = line.trim().is_empty();
+        if !blank && !in_paragraph {
+            paragraphs += 1;
+        }
+        in_paragraph = !blank;
+    }
+    (words, sentences, paragraphs)
+}
+
+#[derive(serde::Serialize)]
+struct NoteWords {
+    file_id: String,
+    words: u64,
+}
+
+/// Corpus statistics over every Markdown note of a vault:
+/// `{ total_words, total_sentences, total_paragraphs, avg_words_per_note,
+/// longest_note, shortest_note }` (the last two are `null` for an empty vault).
+/// Notes that can't be read as UTF-8 text are skipped.
+#[tauri::command]
+fn get_text_statistics(vault_id: &str) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            filter_extensions: Some(vec!["md".to_string()]),
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let mut files = Vec::new();
+    flatten_files(nodes, &mut files);
+
+    let (mut total_words, mut total_sentences, mut total_paragraphs) = (0u64, 0u64, 0u64);
+    let mut longest: Option<NoteWords> = None;
+    let mut shortest: Option<NoteWords> = None;
+    let mut note_count = 0;
+    for f in files {
+        // unreadable or non-UTF-8 notes are skipped, as in get_vault_note_names
+        let Ok(content) = read_text_file(&node_disk_path(&root, &f.id)) else {
+            continue;
+        };
+        note_count += 1;
+        let (words, sentences, paragraphs) = count_text(&markdown_plain_text(&content));
+        total_words += words;
+        total_sentences += sentences;
+        total_paragraphs += paragraphs;
+        if longest.as_ref().map(|n| words > n.words).unwrap_or(true) {
+            longest = Some(NoteWords { file_id: f.id.clone(), words });
+        }
+        if shortest.as_ref().map(|n| words < n.words).unwrap_or(true) {
+            shortest = Some(NoteWords { file_id: f.id, words });
+        }
+    }
+
+    let avg_words_per_note = if note_count == 0 { 0.0 } else { total_words as f64 / note_count as f64 };
+    let out = json!({
+        "total_words": total_words,
+        "total_sentences": total_sentences,
+        "total_paragraphs": total_paragraphs,
+        "avg_words_per_note": avg_words_per_note,
+        "longest_note": longest,
+        "shortest_note": shortest,
+    });
+    serde_json::to_string(&out).map_err(|e| e.to_string())
+}
+
+const READING_WORDS_PER_MINUTE: u64 = 200;
+
+/// Writing statistics of one note, counted on its text with the Markdown
+/// syntax stripped: `{ wordCount, characterCount, characterCountNoSpaces,
+/// paragraphCount, estimatedReadingMinutes }` (reading time rounded up at 200 WPM).
+#[tauri::command]
+fn get_content_stats(file_id: &str) -> Result<String, String> {
+    let content = read_text_file(&resolve_file_path_checked(file_id)?)?;
+    let plain = markdown_plain_text(&content);
+    let text = plain.trim_end();
+    let (words, _, paragraphs) = count_text(text);
+    let out = json!({
+        "wordCount": words,
+        "characterCount": text.chars().count(),
+        "characterCountNoSpaces": text.chars().filter(|c| !c.is_whitespace()).count(),
+        "paragraphCount": paragraphs,
+        "estimatedReadingMinutes": words.div_ceil(READING_WORDS_PER_MINUTE),
+    });
+    Ok(out.to_string())
+}
+
+// ----------------- Templates -----------------
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+struct TemplateMetadata {
+    name: String,
+    source_file_id: Option<String>,
+    created_at_ms: i64,
+}
+
+fn read_templates_metadata() -> Result<Vec<TemplateMetadata>, String> {
+    let mut base = base_dir()?;
+    base.push("templates_metadata.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))
+}
+
+/// Copy a note into `templates/<template_name>.<ext>` (extension taken from
+/// the note, `md` by default) and record where it came from in templates_metadata.json.
+#[tauri::command]
+fn create_template_from_file(file_id: &str, template_name: &str) -> Result<(), String> {
+    if template_name.trim().is_empty() || template_name.contains(['/', '\\']) || template_name.starts_with('.') {
+        return Err(format!("invalid template name: {}", template_name));
+    }
+    let source = resolve_file_path_checked(file_id)?;
+    if !source.is_file() {
+        return Err(format!("file not found: {}", file_id));
+    }
+    let content = read_text_file(&source)?;
+    let ext = source
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "md".to_string());
+
+    let mut target = base_dir()?;
+    target.push("templates");
+    ensure_dir(&target)?;
+    target.push(format!("{}.{}", template_name, ext));
+    if target.exists() {
+        return Err(format!("template already exists: {}", template_name));
+    }
+    write_text_file(&target, &content)?;
+
+    let mut meta = read_templates_metadata()?;
+    meta.retain(|m| m.name != template_name);
+    meta.push(TemplateMetadata {
+        name: template_name.to_string(),
+        source_file_id: Some(file_id.to_string()),
+        created_at_ms: chrono::Utc::now().timestamp_millis(),
+    });
+    let mut meta_path = base_dir()?;
+    meta_path.push("templates_metadata.json");
+    let s = serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?;
+    write_json_file(&meta_path, &s)
+}
+
+/// List templates in `templates/` as `[{ name, file_name, source_file_id, created_at_ms }]`.
+/// Origin fields come from templates_metadata.json and are null for templates
+/// that were added by hand.
+#[tauri::command]
+fn list_templates() -> Result<String, String> {
+    let meta = read_templates_metadata()?;
+    let mut dir = base_dir()?;
+    dir.push("templates");
+    let mut out = Vec::new();
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| file_name.clone());
+            let m = meta.iter().find(|m| m.name == name);
+            out.push(json!({
+                "name": name,
+                "file_name": file_name,
+                "source_file_id": m.and_then(|m| m.source_file_id.clone()),
+                "created_at_ms": m.map(|m| m.created_at_ms),
+            }));
+        }
+    }
+    out.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    serde_json::to_string(&out).map_err(|e| e.to_string())
+}
+
+// ----------------- Links -----------------
+
+/// A link found in a Markdown note: either `[[target|alias]]` (wiki) or
+/// `[text](target)` (Markdown). Embeds (`![[..]]`, `![..](..)`) are included.
+struct MdLink {
+    wiki: bool,
+    target: String,
+    /// `alias` of `[[target|alias]]`, or the link text of `[text](target)`.
+    alias: Option<String>,
+    /// 1-based line number in the note.
+    line: usize,
+}
+
+/// Extract wikilinks and Markdown links from a note, ignoring fenced code blocks.
+fn parse_markdown_links(content: &str) -> Vec<MdLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    for (idx, line) in content.lines().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(open) = rest.find('[') {
+            let after = &rest[open..];
+            if let Some(inner) = after.strip_prefix("[[") {
+                if let Some(close) = inner.find("]]") {
+                    let body = &inner[..close];
+                    let (target, alias) = match body.split_once('|') {
+                        Some((t, a)) => (t, Some(a.trim().to_string())),
+                        None => (body, None),
+                    };
+                    if !target.trim().is_empty() {
+                        links.push(MdLink {
+                            wiki: true,
+                            target: target.trim().to_string(),
+                            alias,
+                            line: idx + 1,
+                        });
+                    }
+                    rest = &inner[close + 2..];
+                    continue;
+                }
+            } else if let Some(close) = after.find("](") {
+                let text = &after[1..close];
+                let url_part = &after[close + 2..];
+                if !text.contains('[') {
+                    if let Some(end) = url_part.find(')') {
+                        // drop an optional title: [text](path "title")
+                        let target = url_part[..end].split_whitespace().next().unwrap_or("");
+                        if !target.is_empty() {
+                            links.push(MdLink {
+                                wiki: false,
+                                target: target.trim_matches(|c| c == '<' || c == '>').to_string(),
+                                alias: Some(text.to_string()).filter(|t| !t.is_empty()),
+                                line: idx + 1,
+                            });
                         }
+                        rest = &url_part[end + 1..];
+                        continue;
+                    }
+                }
+            }
+            rest = &after[1..];
+        }
+    }
+    links
+}
+
+/// Links in one file as `[{ type: "wiki" | "url", target, alias, lineNumber }]`,
+/// where `url` covers every `[text](target)` link. A missing file has no links.
+#[tauri::command]
+fn extract_markdown_links(file_id: &str) -> Result<String, String> {
+    let path = resolve_file_path_checked(file_id)?;
+    let content = read_text_file(&path)?;
+    let out: Vec<serde_json::Value> = parse_markdown_links(&content)
+        .into_iter()
+        .map(|l| {
+            json!({
+                "type": if l.wiki { "wiki" } else { "url" },
+                "target": l.target,
+                "alias": l.alias,
+                "lineNumber": l.line,
+            })
+        })
+        .collect();
+    serde_json::to_string(&out).map_err(|e| e.to_string())
+}
+
+/// Level and text of an ATX heading line (`## Title ##` -> `(2, "Title")`).
+fn parse_atx_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    // an optional closing sequence of #s is not part of the text
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level, text.to_string()))
+}
+
+/// ATX headings (H1–H6) of a file as `[{ level, text, lineNumber }]`, read
+/// line by line. Headings in frontmatter and fenced code blocks are skipped.
+#[tauri::command]
+fn extract_markdown_headings(file_id: &str) -> Result<String, String> {
+    use std::io::BufRead;
+    let path = resolve_file_path_checked(file_id)?;
+    let file = match fs::File::open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok("[]".to_string()),
+        Err(e) => return Err(format!("read error {}: {}", path.display(), e)),
+    };
+
+    let mut headings = Vec::new();
+    let mut in_fence = false;
+    let mut in_frontmatter = false;
+    for (idx, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("read error {}: {}", path.display(), e))?;
+        if idx == 0 && line.trim_end() == "---" {
+            in_frontmatter = true;
+            continue;
+        }
+        if in_frontmatter {
+            in_frontmatter = line.trim_end() != "---";
+            continue;
+        }
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some((level, text)) = parse_atx_heading(&line) {
+            headings.push(json!({ "level": level, "text": text, "lineNumber": idx + 1 }));
+        }
+    }
+    serde_json::to_string(&headings).map_err(|e| e.to_string())
+}
+
+/// True for link targets that point outside the vault (`https://…`, `mailto:…`)
+/// or only at an anchor in the same note.
+fn is_external_link(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:") || target.starts_with('#')
+}
+
+/// Lexically normalise a relative path (`a/./b/../c` -> `a/c`). Returns
+/// `None` when `..` would climb above the starting point.
+fn normalize_relative(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            p => parts.push(p),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Decode the `%XX` escapes commonly used in Markdown link paths.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Resolves link targets to file IDs within one filesystem vault.
+struct LinkResolver {
+    root: PathBuf,
+    files: Vec<FileSystemNode>,
+    /// lowercased relative path / path without extension / name / stem -> file ID
+    by_key: HashMap<String, String>,
+    /// lowercased frontmatter alias -> file ID, built on first use
+    by_alias: Option<HashMap<String, String>>,
+}
+
+impl LinkResolver {
+    fn new(vault_id: &str) -> Result<Self, String> {
+        let root = vault_root(vault_id)?;
+        let opts = configured_scan_options(&root, &ScanOptions::default());
+        let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+        let mut files = Vec::new();
+        flatten_files(nodes, &mut files);
+
+        let mut by_key = HashMap::new();
+        for f in &files {
+            let rel = f.id.split_once(':').map(|(_, r)| r).unwrap_or(&f.id).to_lowercase();
+            let stem = |s: &str| s.rsplit_once('.').map(|(a, _)| a.to_string()).unwrap_or_else(|| s.to_string());
+            let name = f.name.to_lowercase();
+            for key in [stem(&rel), rel.clone(), stem(&name), name] {
+                by_key.entry(key).or_insert_with(|| f.id.clone());
+            }
+        }
+        Ok(LinkResolver { root, files, by_key, by_alias: None })
+    }
+
+    fn alias_index(&mut self) -> &HashMap<String, String> {
+        let root = &self.root;
+        let files = &self.files;
+        self.by_alias.get_or_insert_with(|| {
+            let mut map = HashMap::new();
+            for f in files.iter().filter(|f| f.name.ends_with(".md")) {
+                let content = read_text_file(&node_disk_path(root, &f.id)).unwrap_or_default();
+                if let Some(fm) = split_frontmatter(&content).0 {
+                    for alias in frontmatter_list(fm, "aliases") {
+                        map.entry(alias.to_lowercase()).or_insert_with(|| f.id.clone());
                     }
                 }
             }
+            map
+        })
+    }
+
+    /// Resolve a `[[wikilink]]` target (heading / block refs are ignored).
+    fn resolve_wiki(&mut self, target: &str) -> Option<String> {
+        let target = target.split('#').next().unwrap_or(target).trim();
+        let key = target.replace('\\', "/").trim_start_matches('/').to_lowercase();
+        if let Some(id) = self.by_key.get(&key) {
+            return Some(id.clone());
+        }
+        self.alias_index().get(&key).cloned()
+    }
+
+    /// Resolve a Markdown link target relative to the linking note's folder,
+    /// falling back to vault-root and name based resolution.
+    fn resolve_markdown(&mut self, target: &str, source_id: &str) -> Option<String> {
+        if is_external_link(target) {
+            return None;
+        }
+        let target = percent_decode(target.split('#').next().unwrap_or(target));
+        let source_rel = source_id.split_once(':').map(|(_, r)| r).unwrap_or(source_id);
+        let source_dir = source_rel.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+        let joined = if target.starts_with('/') {
+            target.clone()
+        } else {
+            format!("{}/{}", source_dir, target)
+        };
+        if let Some(rel) = normalize_relative(&joined) {
+            if let Some(id) = self.by_key.get(&rel.to_lowercase()) {
+                return Some(id.clone());
+            }
+        }
+        self.resolve_wiki(&target)
+    }
+
+    fn resolve(&mut self, link: &MdLink, source_id: &str) -> Option<String> {
+        if link.wiki {
+            self.resolve_wiki(&link.target)
+        } else {
+            self.resolve_markdown(&link.target, source_id)
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct LinkedFile {
+    file_id: Option<String>,
+    link_text: String,
+    resolved: bool,
+}
+
+/// Return the outbound links of a note as `[{ file_id, link_text, resolved }]`.
+/// External URLs are not included; links that don't match any file in the
+/// vault have `resolved: false` and `file_id: null`.
+#[tauri::command]
+fn get_linked_files(vault_id: &str, file_id: &str) -> Result<String, String> {
+    let content = read_text_file(&resolve_file_path_checked(file_id)?)?;
+    let mut resolver = LinkResolver::new(vault_id)?;
+    let linked: Vec<LinkedFile> = parse_markdown_links(&content)
+        .into_iter()
+        .filter(|l| l.wiki || !is_external_link(&l.target))
+        .map(|l| {
+            let id = resolver.resolve(&l, file_id);
+            LinkedFile {
+                resolved: id.is_some(),
+                file_id: id,
+                link_text: l.target,
+            }
+        })
+        .collect();
+    serde_json::to_string(&linked).map_err(|e| e.to_string())
+}
+
+#[derive(serde::Serialize)]
+struct NoteName {
+    file_id: String,
+    name: String,
+    aliases: Vec<String>,
+}
+
+/// Return every Markdown note of a vault as `[{ file_id, name, aliases }]`,
+/// where `name` is the file name without `.md`. Meant to be loaded once on
+/// vault open to back wikilink autocomplete.
+#[tauri::command]
+fn get_vault_note_names(vault_id: &str) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let opts = configured_scan_options(
+        &root,
+        &ScanOptions {
+            filter_extensions: Some(vec!["md".to_string()]),
+            ..Default::default()
+        },
+    );
+    let nodes = scan_directory(&root, &root, None, &format!("{}:", vault_id), &opts)?;
+    let mut files = Vec::new();
+    flatten_files(nodes, &mut files);
+
+    let notes: Vec<NoteName> = files
+        .into_iter()
+        .map(|f| {
+            let content = read_text_file(&node_disk_path(&root, &f.id)).unwrap_or_default();
+            let aliases = split_frontmatter(&content)
+                .0
+                .map(|fm| frontmatter_list(fm, "aliases"))
+                .unwrap_or_default();
+            NoteName {
+                name: f.name.strip_suffix(".md").unwrap_or(&f.name).to_string(),
+                file_id: f.id,
+                aliases,
+            }
+        })
+        .collect();
+    serde_json::to_string(&notes).map_err(|e| e.to_string())
+}
+
+/// True when a file name contains a `YYYY-MM-DD` date, the daily-note convention.
+fn is_daily_note_name(name: &str) -> bool {
+    name.as_bytes().windows(10).any(|w| {
+        w.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+    })
+}
+
+#[derive(serde::Serialize)]
+struct OrphanedFile {
+    file_id: String,
+    name: String,
+    size_bytes: u64,
+    last_modified_ms: Option<i64>,
+}
+
+/// List files that nothing links to and that link to nothing themselves,
+/// excluding daily notes and files referenced from a canvas. Returns
+/// `[{ file_id, name, size_bytes, last_modified_ms }]`, oldest first.
+#[tauri::command]
+fn get_vault_orphaned_files(vault_id: &str) -> Result<String, String> {
+    let mut resolver = LinkResolver::new(vault_id)?;
+    let root = resolver.root.clone();
+    let files = resolver.files.clone();
+
+    let mut inbound: HashSet<String> = HashSet::new();
+    let mut outbound: HashSet<String> = HashSet::new();
+    // `file` fields of canvas nodes, which hold a file ID or vault-relative path
+    let mut canvas_refs: HashSet<String> = HashSet::new();
+    for f in &files {
+        let path = node_disk_path(&root, &f.id);
+        if f.name.ends_with(".canvas") {
+            let canvas: serde_json::Value = serde_json::from_str(&read_text_file(&path).unwrap_or_default()).unwrap_or_default();
+            let nodes = canvas.get("nodes").and_then(|n| n.as_array()).map(|a| a.as_slice()).unwrap_or_default();
+            canvas_refs.extend(nodes.iter().filter_map(|n| n.get("file").and_then(|x| x.as_str())).map(String::from));
+            continue;
+        }
+        if !f.name.ends_with(".md") {
+            continue;
+        }
+        let content = read_text_file(&path).unwrap_or_default();
+        for link in parse_markdown_links(&content) {
+            if let Some(target) = resolver.resolve(&link, &f.id) {
+                outbound.insert(f.id.clone());
+                inbound.insert(target);
+            }
+        }
+    }
+
+    let mut orphans: Vec<OrphanedFile> = files
+        .into_iter()
+        .filter(|f| !inbound.contains(&f.id) && !outbound.contains(&f.id))
+        .filter(|f| !is_daily_note_name(&f.name))
+        .filter(|f| {
+            let rel = f.id.split_once(':').map(|(_, r)| r).unwrap_or(&f.id);
+            !canvas_refs.contains(&f.id) && !canvas_refs.contains(rel)
+        })
+        .map(|f| {
+            let meta = fs::metadata(node_disk_path(&root, &f.id)).ok();
+            OrphanedFile {
+                size_bytes: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+                last_modified_ms: meta.as_ref().and_then(modified_ms),
+                file_id: f.id,
+                name: f.name,
+            }
+        })
+        .collect();
+    orphans.sort_by_key(|f| f.last_modified_ms.unwrap_or(i64::MAX));
+    serde_json::to_string(&orphans).map_err(|e| e.to_string())
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BacklinkCache {
+    /// file ID -> modification time when the index was built
+    mtimes: HashMap<String, i64>,
+    /// link target -> IDs of the notes linking to it
+    backlinks: HashMap<String, Vec<String>>,
+}
+
+/// Map every link target to the notes that link to it:
+/// `{ "<target>": ["<source id>", ...] }`. Targets are file IDs when the link
+/// resolves and the raw link text otherwise; external URLs are left out.
+/// The index is cached in `.focosx/backlinks.json` and reused until a file
+/// is added, removed or modified.
+#[tauri::command]
+fn build_backlink_index(vault_id: &str) -> Result<String, String> {
+    let mut resolver = LinkResolver::new(vault_id)?;
+    let root = resolver.root.clone();
+    let files = resolver.files.clone();
+    let mtimes: HashMap<String, i64> = files
+        .iter()
+        .map(|f| {
+            let ms = fs::metadata(node_disk_path(&root, &f.id)).ok().as_ref().and_then(modified_ms);
+            (f.id.clone(), ms.unwrap_or(0))
+        })
+        .collect();
+
+    let cache_path = root.join(".focosx").join("backlinks.json");
+    let cached: Option<BacklinkCache> = read_json_file(&cache_path).ok().and_then(|s| serde_json::from_str(&s).ok());
+    if let Some(cache) = cached.filter(|c| c.mtimes == mtimes) {
+        return serde_json::to_string(&cache.backlinks).map_err(|e| e.to_string());
+    }
+
+    let mut backlinks: HashMap<String, Vec<String>> = HashMap::new();
+    for f in files.iter().filter(|f| f.name.ends_with(".md")) {
+        let content = read_text_file(&node_disk_path(&root, &f.id)).unwrap_or_default();
+        for link in parse_markdown_links(&content) {
+            if !link.wiki && is_external_link(&link.target) {
+                continue;
+            }
+            let target = resolver.resolve(&link, &f.id).unwrap_or_else(|| link.target.clone());
+            let sources = backlinks.entry(target).or_default();
+            if !sources.contains(&f.id) {
+                sources.push(f.id.clone());
+            }
+        }
+    }
+
+    let cache = BacklinkCache { mtimes, backlinks };
+    let out = serde_json::to_string(&cache).map_err(|e| e.to_string())?;
+    write_json_file(&cache_path, &out)?;
+    serde_json::to_string(&cache.backlinks).map_err(|e| e.to_string())
+}
+
+// ----------------- Vault lint -----------------
+
+#[derive(serde::Serialize)]
+struct LintIssue {
+    rule: String,
+    file_id: String,
+    line: Option<usize>,
+    message: String,
+}
+
+/// A lint rule: given the vault's link resolver, a note ID and its content,
+/// return `(line, message)` pairs for every problem found.
+type LintRule = fn(&mut LinkResolver, &str, &str) -> Vec<(Option<usize>, String)>;
+
+const LINT_RULES: &[(&str, LintRule)] = &[
+    ("no_broken_links", lint_no_broken_links),
+    ("no_empty_notes", lint_no_empty_notes),
+    ("consistent_heading_style", lint_consistent_heading_style),
+    ("no_trailing_whitespace", lint_no_trailing_whitespace),
+    ("frontmatter_required", lint_frontmatter_required),
+];
+
+/// Number of lines taken by the frontmatter block (0 when there is none).
+fn frontmatter_line_count(content: &str) -> usize {
+    match split_frontmatter(content) {
+        (Some(_), body) => content[..content.len() - body.len()].lines().count(),
+        (None, _) => 0,
+    }
+}
+
+fn lint_no_broken_links(resolver: &mut LinkResolver, file_id: &str, content: &str) -> Vec<(Option<usize>, String)> {
+    parse_markdown_links(content)
+        .into_iter()
+        .filter(|l| l.wiki || !is_external_link(&l.target))
+        .filter(|l| resolver.resolve(l, file_id).is_none())
+        .map(|l| (Some(l.line), format!("Link target \"{}\" does not exist", l.target)))
+        .collect()
+}
+
+fn lint_no_empty_notes(_: &mut LinkResolver, _: &str, content: &str) -> Vec<(Option<usize>, String)> {
+    if split_frontmatter(content).1.trim().is_empty() {
+        vec![(None, "Note is empty".to_string())]
+    } else {
+        vec![]
+    }
+}
+
+/// Flags headings whose style (ATX `# Title` vs. setext underline) differs
+/// from the first heading of the note.
+fn lint_consistent_heading_style(_: &mut LinkResolver, _: &str, content: &str) -> Vec<(Option<usize>, String)> {
+    let skip = frontmatter_line_count(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut expected: Option<bool> = None; // Some(true) = ATX
+    let mut issues = Vec::new();
+    let mut in_fence = false;
+    for i in skip..lines.len() {
+        let line = lines[i];
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let trimmed = line.trim();
+        let is_atx = trimmed.starts_with('#') && trimmed.trim_start_matches('#').starts_with(' ');
+        let is_setext = i > skip
+            && !lines[i - 1].trim().is_empty()
+            && trimmed.len() >= 2
+            && (trimmed.chars().all(|c| c == '=') || trimmed.chars().all(|c| c == '-'));
+        if !is_atx && !is_setext {
+            continue;
+        }
+        match expected {
+            None => expected = Some(is_atx),
+            Some(atx) if atx != is_atx => {
+                let (used, wanted) = if is_atx { ("ATX", "setext") } else { ("setext", "ATX") };
+                issues.push((Some(i + 1), format!("{} heading in a note that uses {} headings", used, wanted)));
+            }
+            _ => {}
         }
     }
+    issues
+}
+
+fn lint_no_trailing_whitespace(_: &mut LinkResolver, _: &str, content: &str) -> Vec<(Option<usize>, String)> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| l.ends_with(' ') || l.ends_with('\t'))
+        .map(|(i, _)| (Some(i + 1), "Trailing whitespace".to_string()))
+        .collect()
+}
+
+fn lint_frontmatter_required(_: &mut LinkResolver, _: &str, content: &str) -> Vec<(Option<usize>, String)> {
+    if split_frontmatter(content).0.is_none() {
+        vec![(Some(1), "Note has no frontmatter".to_string())]
+    } else {
+        vec![]
+    }
+}
+
+/// Run the given lint rules over every Markdown note of a vault. Returns
+/// `{ issues: [{ rule, file_id, line, message }], issues_by_rule, total_issues }`.
+#[tauri::command]
+fn lint_vault(vault_id: &str, rules: Vec<String>) -> Result<String, String> {
+    let selected: Vec<(&str, LintRule)> = rules
+        .iter()
+        .map(|r| {
+            LINT_RULES
+                .iter()
+                .find(|(name, _)| name == r)
+                .copied()
+                .ok_or_else(|| format!("Unknown lint rule: {}", r))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut resolver = LinkResolver::new(vault_id)?;
+    let notes: Vec<String> = resolver.files.iter().filter(|f| f.name.ends_with(".md")).map(|f| f.id.clone()).collect();
+    let mut issues = Vec::new();
+    for file_id in &notes {
+        let content = read_text_file(&node_disk_path(&resolver.root, file_id))?;
+        for (rule, check) in &selected {
+            for (line, message) in check(&mut resolver, file_id, &content) {
+                issues.push(LintIssue {
+                    rule: rule.to_string(),
+                    file_id: file_id.clone(),
+                    line,
+                    message,
+                });
+            }
+        }
+    }
+
+    let mut issues_by_rule: HashMap<String, usize> = selected.iter().map(|(r, _)| (r.to_string(), 0)).collect();
+    for issue in &issues {
+        *issues_by_rule.entry(issue.rule.clone()).or_insert(0) += 1;
+    }
+    let out = json!({
+        "total_issues": issues.len(),
+        "issues_by_rule": issues_by_rule,
+        "issues": issues,
+    });
+    serde_json::to_string(&out).map_err(|e| e.to_string())
+}
+
+// ----------------- Plugins (global / workspace / remote) -----------------
+
+#[tauri::command]
+fn get_global_plugin_ids() -> Result<String, String> {
+    let mut base = base_dir()?;
+    base.push("global_plugins.json");
+    read_json_file(&base)
+}
+
+#[tauri::command]
+fn save_global_plugin_ids(json: String) -> Result<(), String> {
+    let mut base = base_dir()?;
+    base.push("global_plugins.json");
+    ensure_dir(base.parent().unwrap_or(Path::new("/")))?;
+    write_json_file(&base, &json)
+}
+
+/// Plugin IDs enabled for a vault. With `merge_global` the global plugins are
+/// included too: globals first, then workspace plugins, without duplicates.
+#[tauri::command]
+fn get_workspace_plugin_ids(vault_id: &str, merge_global: Option<bool>) -> Result<String, String> {
+    let mut base = base_dir()?;
+    base.push("workspace_plugins");
+    ensure_dir(&base)?;
+    base.push(format!("{}.json", vault_id));
+    if !merge_global.unwrap_or(false) {
+        return read_json_file(&base);
+    }
+
+    let global = base_dir()?.join("global_plugins.json");
+    let mut ids = read_plugin_id_list(&global)?;
+    for id in read_plugin_id_list(&base)? {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    serde_json::to_string(&ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_workspace_plugin_ids(vault_id: &str, json: String) -> Result<(), String> {
+    let mut base = base_dir()?;
+    base.push("workspace_plugins");
+    ensure_dir(&base)?;
+    base.push(format!("{}.json", vault_id));
+    write_json_file(&base, &json)
+}
+
+// Remote installed plugin objects: stored as an array in remote_plugins.json
+#[tauri::command]
+fn get_installed_remote_plugins() -> Result<String, String> {
+    let mut base = base_dir()?;
+    base.push("remote_plugins.json");
+    read_json_file(&base)
+}
+
+/// Parse `MAJOR.MINOR.PATCH` with an optional `-prerelease` and/or `+build`
+/// suffix into a key that orders versions: the numeric parts, then `true`
+/// for releases so `1.0.0` sorts above `1.0.0-beta`.
+fn parse_semver(version: &str) -> Option<(u64, u64, u64, bool)> {
+    let core = version.split('+').next()?;
+    let (core, pre) = match core.split_once('-') {
+        Some((c, _)) => (c, true),
+        None => (core, false),
+    };
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(|p| if p.chars().all(|c| c.is_ascii_digit()) { p.parse().ok() } else { None })
+        .collect::<Option<_>>()?;
+    match parts[..] {
+        [major, minor, patch] => Some((major, minor, patch, !pre)),
+        _ => None,
+    }
+}
+
+/// Install or replace a remote plugin. The object needs an `id` and a semver
+/// `version`; `installedAt` is set here, and replacing a plugin records the
+/// old version as `previousVersion`.
+#[tauri::command]
+fn save_installed_remote_plugin(plugin_json: String) -> Result<(), String> {
+    // plugin_json is expected to be a JSON object with { id, code, manifestUrl, version }
+    let mut base = base_dir()?;
+    base.push("remote_plugins.json");
+    ensure_dir(&base.parent().unwrap_or(Path::new("/")))?;
+    // read current
+    let current = read_json_file(&base)?;
+    let mut vec: Vec<serde_json::Value> = if current.trim().is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(&current).map_err(|e| format!("parse error: {}", e))?
+    };
+    let mut plugin_val: serde_json::Value =
+        serde_json::from_str(&plugin_json).map_err(|e| format!("invalid plugin json: {}", e))?;
+    let id = match plugin_val.get("id").and_then(|v| v.as_str()) {
+        Some(id) => id.to_string(),
+        None => return Err("plugin json must include an 'id' field".to_string()),
+    };
+    match plugin_val.get("version").and_then(|v| v.as_str()) {
+        Some(v) if parse_semver(v).is_some() => {}
+        Some(v) => return Err(format!("plugin version is not a semver string: {}", v)),
+        None => return Err("plugin json must include a 'version' field".to_string()),
+    }
+    let obj = plugin_val.as_object_mut().ok_or("plugin json must be an object")?;
+    obj.insert("installedAt".to_string(), json!(chrono::Utc::now().timestamp_millis()));
+    // replace if exists by id, otherwise push
+    if let Some(pos) = vec
+        .iter()
+        .position(|p| p.get("id").and_then(|x| x.as_str()) == Some(id.as_str()))
+    {
+        if let Some(prev) = vec[pos].get("version").cloned() {
+            obj.insert("previousVersion".to_string(), prev);
+        }
+        // updating a disabled plugin must not re-enable it
+        if let Some(enabled) = vec[pos].get("enabled").cloned() {
+            obj.entry("enabled").or_insert(enabled);
+        }
+        vec[pos] = plugin_val;
+    } else {
+        vec.push(plugin_val);
+    }
+    let s = serde_json::to_string_pretty(&vec).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)
+}
+
+/// Fetch the manifest of every installed remote plugin and list those whose
+/// manifest `version` is newer than the installed one. Plugins whose manifest
+/// cannot be fetched or has no valid version are listed with an `error`
+/// instead, so they aren't mistaken for being up to date.
+fn find_remote_plugin_updates() -> Result<Vec<serde_json::Value>, String> {
+    let mut base = base_dir()?;
+    base.push("remote_plugins.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let plugins: Vec<serde_json::Value> = serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut updates = Vec::new();
+    for p in &plugins {
+        let (Some(id), Some(url)) = (
+            p.get("id").and_then(|x| x.as_str()),
+            p.get("manifestUrl").and_then(|x| x.as_str()),
+        ) else {
+            continue;
+        };
+        let current = p.get("version").and_then(|x| x.as_str()).unwrap_or("0.0.0");
+        let failed = |error: String| {
+            json!({
+                "id": id,
+                "currentVersion": current,
+                "manifestUrl": url,
+                "error": error,
+            })
+        };
+        let manifest: serde_json::Value = match client.get(url).send().and_then(|r| r.error_for_status()).and_then(|r| r.json()) {
+            Ok(m) => m,
+            Err(e) => {
+                updates.push(failed(format!("failed to fetch {}: {}", url, e)));
+                continue;
+            }
+        };
+        let Some((latest, l)) = manifest
+            .get("version")
+            .and_then(|x| x.as_str())
+            .and_then(|v| parse_semver(v).map(|l| (v, l)))
+        else {
+            updates.push(failed("manifest has no valid version".to_string()));
+            continue;
+        };
+        if let Some(c) = parse_semver(current) {
+            if l > c {
+                updates.push(json!({
+                    "id": id,
+                    "currentVersion": current,
+                    "latestVersion": latest,
+                    "manifestUrl": url,
+                }));
+            }
+        }
+    }
+    Ok(updates)
+}
+
+/// Remote plugins with a newer version available, as
+/// `[{ id, currentVersion, latestVersion, manifestUrl }]`, plus
+/// `{ id, currentVersion, manifestUrl, error }` for plugins that could not
+/// be checked. The requests run on a blocking worker so the UI thread stays
+/// responsive.
+#[tauri::command]
+async fn check_remote_plugin_updates() -> Result<String, String> {
+    let updates = tauri::async_runtime::spawn_blocking(find_remote_plugin_updates)
+        .await
+        .map_err(|e| e.to_string())??;
+    serde_json::to_string(&updates).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn remove_installed_remote_plugin(id: &str) -> Result<(), String> {
+    let mut base = base_dir()?;
+    base.push("remote_plugins.json");
+    let cur = read_json_file(&base)?;
+    if cur.trim().is_empty() {
+        return Ok(());
+    }
+    let mut vec: Vec<serde_json::Value> =
+        serde_json::from_str(&cur).map_err(|e| format!("parse error: {}", e))?;
+    vec.retain(|p| p.get("id").and_then(|x| x.as_str()) != Some(id));
+    let s = serde_json::to_string_pretty(&vec).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)?;
+    delete_plugin_config(id)
+}
+
+/// Set `enabled` on a remote plugin's entry in remote_plugins.json.
+fn set_remote_plugin_enabled(plugin_id: &str, enabled: bool) -> Result<(), String> {
+    let mut base = base_dir()?;
+    base.push("remote_plugins.json");
+    let cur = read_json_file(&base)?;
+    let mut vec: Vec<serde_json::Value> = if cur.trim().is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(&cur).map_err(|e| format!("parse error: {}", e))?
+    };
+    let plugin = vec
+        .iter_mut()
+        .find(|p| p.get("id").and_then(|x| x.as_str()) == Some(plugin_id))
+        .and_then(|p| p.as_object_mut())
+        .ok_or_else(|| format!("plugin not installed: {}", plugin_id))?;
+    plugin.insert("enabled".to_string(), json!(enabled));
+    let s = serde_json::to_string_pretty(&vec).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)
+}
+
+/// Re-enable a disabled remote plugin.
+#[tauri::command]
+fn enable_plugin(plugin_id: &str) -> Result<(), String> {
+    set_remote_plugin_enabled(plugin_id, true)
+}
+
+/// Turn a remote plugin off without uninstalling it; its code and config are kept.
+#[tauri::command]
+fn disable_plugin(plugin_id: &str) -> Result<(), String> {
+    set_remote_plugin_enabled(plugin_id, false)
+}
+
+/// `plugin_configs/<pluginId>.json` in the app data dir. Rejects IDs that are
+/// not a plain file name.
+fn plugin_config_path(plugin_id: &str) -> Result<PathBuf, String> {
+    if plugin_id.is_empty() || plugin_id.contains(['/', '\\']) || plugin_id == "." || plugin_id == ".." {
+        return Err(format!("invalid plugin id: {}", plugin_id));
+    }
+    let mut base = base_dir()?;
+    base.push("plugin_configs");
+    base.push(format!("{}.json", plugin_id));
+    Ok(base)
+}
 
-    // Fallback: write to app-managed trees folder (for non-filesystem vaults)
-    base.push("trees");
-    ensure_dir(&base)?;
-    base.push(format!("{}.json", vault_id));
-    write_json_file(&base, &json)
+/// Read a plugin's stored configuration (empty string if none was saved).
+#[tauri::command]
+fn get_plugin_config(plugin_id: &str) -> Result<String, String> {
+    read_json_file(&plugin_config_path(plugin_id)?)
 }
 
-// ----------------- File Contents -----------------
+/// Store configuration for an installed remote plugin.
+#[tauri::command]
+fn save_plugin_config(plugin_id: &str, json: String) -> Result<(), String> {
+    let path = plugin_config_path(plugin_id)?;
+    let raw = read_json_file(&base_dir()?.join("remote_plugins.json"))?;
+    let installed: Vec<serde_json::Value> = if raw.trim().is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))?
+    };
+    if !installed.iter().any(|p| p.get("id").and_then(|x| x.as_str()) == Some(plugin_id)) {
+        return Err(format!("plugin not installed: {}", plugin_id));
+    }
+    write_json_file(&path, &json)
+}
 
+/// Remove a plugin's stored configuration; missing files are not an error.
 #[tauri::command]
-fn load_file_content(file_id: &str) -> Result<String, String> {
-    // Check if file_id contains vault prefix (vaultId:path)
-    if let Some((vault_id, path)) = file_id.split_once(':') {
-        let mut base = base_dir()?;
-        base.push("vaults.json");
-        let vraw = read_json_file(&base)?;
-        if !vraw.trim().is_empty() {
-            let vs: serde_json::Value = serde_json::from_str(&vraw).map_err(|e| e.to_string())?;
-            if let Some(arr) = vs.as_array() {
-                for v in arr {
-                    if v.get("id").and_then(|x| x.as_str()) == Some(vault_id) {
-                        if let Some(p) = v.get("path").and_then(|x| x.as_str()) {
-                            let mut file_path = PathBuf::from(p);
-                            file_path.push(path);
-                            return read_text_file(&file_path);
-                        }
-                    }
-                }
-            }
-        }
+fn delete_plugin_config(plugin_id: &str) -> Result<(), String> {
+    let path = plugin_config_path(plugin_id)?;
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("failed to delete {}: {}", path.display(), e)),
+        _ => Ok(()),
     }
+}
 
-    // Legacy/Fallback logic
-    // If the file is part of a vault folder on disk, read from that vault's
-    // `.focosx/contents/<fileId>.json` so content is co-located with the vault.
-    if let Ok(Some(vpath)) = find_vault_folder_for_file(file_id) {
-        let mut content_path = vpath;
-        content_path.push(".focosx");
-        // ensure .focosx/contents exists (read_json_file will tolerate missing file)
-        let mut contents_dir = content_path.clone();
-        contents_dir.push("contents");
-        let _ = ensure_dir(&contents_dir);
-        content_path.push("contents");
-        content_path.push(format!("{}.json", file_id));
-        return read_json_file(&content_path);
+/// Read a JSON array of plugin IDs (global_plugins.json / workspace_plugins/<id>.json).
+fn read_plugin_id_list(path: &Path) -> Result<Vec<String>, String> {
+    let raw = read_json_file(path)?;
+    if raw.trim().is_empty() {
+        return Ok(vec![]);
     }
+    serde_json::from_str(&raw).map_err(|e| format!("parse error {}: {}", path.display(), e))
+}
 
-    let mut base = base_dir()?;
-    base.push("contents");
-    ensure_dir(&base)?;
-    base.push(format!("{}.json", file_id));
-    read_json_file(&base)
+#[derive(serde::Serialize)]
+struct InstalledPlugin {
+    id: String,
+    source: &'static str,
+    code: Option<String>,
+    manifest_url: Option<String>,
+    enabled: bool,
 }
 
+/// Merge global, workspace and remote plugins into one list of
+/// `{ id, source, code, manifest_url, enabled }`, deduplicated by ID.
+/// Precedence is global < workspace < remote; the first position of an ID is kept.
 #[tauri::command]
-fn save_file_content(file_id: &str, json: String) -> Result<(), String> {
-    // Check if file_id contains vault prefix (vaultId:path)
-    if let Some((vault_id, path)) = file_id.split_once(':') {
-        let mut base = base_dir()?;
-        base.push("vaults.json");
-        let vraw = read_json_file(&base)?;
-        if !vraw.trim().is_empty() {
-            let vs: serde_json::Value = serde_json::from_str(&vraw).map_err(|e| e.to_string())?;
-            if let Some(arr) = vs.as_array() {
-                for v in arr {
-                    if v.get("id").and_then(|x| x.as_str()) == Some(vault_id) {
-                        if let Some(p) = v.get("path").and_then(|x| x.as_str()) {
-                            let mut file_path = PathBuf::from(p);
-                            file_path.push(path);
-                            return write_text_file(&file_path, &json);
-                        }
-                    }
-                }
-            }
+fn get_all_installed_plugins(vault_id: &str) -> Result<String, String> {
+    let base = base_dir()?;
+    let mut merged: Vec<InstalledPlugin> = Vec::new();
+    let mut upsert = |plugin: InstalledPlugin| match merged.iter().position(|p| p.id == plugin.id) {
+        Some(pos) => merged[pos] = plugin,
+        None => merged.push(plugin),
+    };
+
+    for id in read_plugin_id_list(&base.join("global_plugins.json"))? {
+        upsert(InstalledPlugin { id, source: "global", code: None, manifest_url: None, enabled: true });
+    }
+    let workspace_path = base.join("workspace_plugins").join(format!("{}.json", vault_id));
+    for id in read_plugin_id_list(&workspace_path)? {
+        upsert(InstalledPlugin { id, source: "workspace", code: None, manifest_url: None, enabled: true });
+    }
+    let raw = read_json_file(&base.join("remote_plugins.json"))?;
+    if !raw.trim().is_empty() {
+        let remote: Vec<serde_json::Value> = serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))?;
+        for p in remote {
+            let id = match p.get("id").and_then(|x| x.as_str()) {
+                Some(id) => id.to_string(),
+                None => continue,
+            };
+            upsert(InstalledPlugin {
+                id,
+                source: "remote",
+                code: p.get("code").and_then(|x| x.as_str()).map(str::to_string),
+                manifest_url: p.get("manifestUrl").and_then(|x| x.as_str()).map(str::to_string),
+                enabled: p.get("enabled").and_then(|x| x.as_bool()).unwrap_or(true),
+            });
         }
     }
+    serde_json::to_string(&merged).map_err(|e| e.to_string())
+}
 
-    // Legacy/Fallback logic
-    // If the file belongs to a vault folder on disk, write into that vault's
-    // `.focosx/contents/<fileId>.json` so user files and metadata live together.
-    if let Ok(Some(vpath)) = find_vault_folder_for_file(file_id) {
-        let mut content_path = vpath;
-        content_path.push(".focosx");
-        ensure_dir(&content_path)?;
-        content_path.push("contents");
-        ensure_dir(&content_path)?;
-        content_path.push(format!("{}.json", file_id));
-        return write_json_file(&content_path, &json);
+/// Load plugin_usage_stats.json (plugin_id -> activation count), empty if missing.
+fn read_plugin_usage_stats() -> Result<HashMap<String, usize>, String> {
+    let mut base = base_dir()?;
+    base.push("plugin_usage_stats.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(HashMap::new());
     }
+    serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))
+}
 
+/// Count one local activation of a plugin. Usage never leaves the machine.
+#[tauri::command]
+fn increment_plugin_usage(plugin_id: &str) -> Result<(), String> {
+    let mut stats = read_plugin_usage_stats()?;
+    *stats.entry(plugin_id.to_string()).or_insert(0) += 1;
     let mut base = base_dir()?;
-    base.push("contents");
-    ensure_dir(&base)?;
-    base.push(format!("{}.json", file_id));
-    write_json_file(&base, &json)
+    base.push("plugin_usage_stats.json");
+    let s = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+    write_json_file(&base, &s)
 }
 
-// ----------------- Plugins (global / workspace / remote) -----------------
+/// Return the full plugin_id -> activation count map as a JSON object.
+#[tauri::command]
+fn get_plugin_usage_stats() -> Result<String, String> {
+    let stats = read_plugin_usage_stats()?;
+    serde_json::to_string(&stats).map_err(|e| e.to_string())
+}
+
+// ----------------- AI Dock Config -----------------
 
 #[tauri::command]
-fn get_global_plugin_ids() -> Result<String, String> {
+fn get_ai_dock_config() -> Result<String, String> {
     let mut base = base_dir()?;
-    base.push("global_plugins.json");
+    base.push("ai_dock.json");
     read_json_file(&base)
 }
 
 #[tauri::command]
-fn save_global_plugin_ids(json: String) -> Result<(), String> {
+fn save_ai_dock_config(json: String) -> Result<(), String> {
     let mut base = base_dir()?;
-    base.push("global_plugins.json");
-    ensure_dir(base.parent().unwrap_or(Path::new("/")))?;
+    base.push("ai_dock.json");
     write_json_file(&base, &json)
 }
 
-#[tauri::command]
-fn get_workspace_plugin_ids(vault_id: &str) -> Result<String, String> {
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AiConversationSummary {
+    id: String,
+    created_at: i64,
+    updated_at: i64,
+    title: String,
+}
+
+/// `ai_conversations/` in the app data dir; conversations are stored as
+/// `<conversationId>.json` next to an `index.json` of summaries.
+fn ai_conversations_dir() -> Result<PathBuf, String> {
     let mut base = base_dir()?;
-    base.push("workspace_plugins");
+    base.push("ai_conversations");
     ensure_dir(&base)?;
-    base.push(format!("{}.json", vault_id));
-    read_json_file(&base)
+    Ok(base)
+}
+
+fn ai_conversation_path(conversation_id: &str) -> Result<PathBuf, String> {
+    if conversation_id.is_empty() || conversation_id.contains(['/', '\\']) || conversation_id.starts_with('.') || conversation_id == "index" {
+        return Err(format!("invalid conversation id: {}", conversation_id));
+    }
+    Ok(ai_conversations_dir()?.join(format!("{}.json", conversation_id)))
 }
 
+fn read_ai_conversation_index() -> Result<Vec<AiConversationSummary>, String> {
+    let raw = read_json_file(&ai_conversations_dir()?.join("index.json"))?;
+    if raw.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&raw).map_err(|e| format!("parse error: {}", e))
+}
+
+fn write_ai_conversation_index(index: &[AiConversationSummary]) -> Result<(), String> {
+    let s = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    write_json_file(&ai_conversations_dir()?.join("index.json"), &s)
+}
+
+/// Store a conversation and update its index entry. The index title is taken
+/// from the conversation's `title` field when present.
 #[tauri::command]
-fn save_workspace_plugin_ids(vault_id: &str, json: String) -> Result<(), String> {
+fn save_ai_conversation(conversation_id: &str, json: String) -> Result<(), String> {
+    let path = ai_conversation_path(conversation_id)?;
+    let value: serde_json::Value = serde_json::from_str(&json).map_err(|e| format!("invalid conversation json: {}", e))?;
+    write_json_file(&path, &json)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let title = value.get("title").and_then(|t| t.as_str()).map(str::to_string);
+    let mut index = read_ai_conversation_index()?;
+    match index.iter_mut().find(|c| c.id == conversation_id) {
+        Some(entry) => {
+            entry.updated_at = now;
+            if let Some(title) = title {
+                entry.title = title;
+            }
+        }
+        None => index.push(AiConversationSummary {
+            id: conversation_id.to_string(),
+            created_at: now,
+            updated_at: now,
+            title: title.unwrap_or_default(),
+        }),
+    }
+    write_ai_conversation_index(&index)
+}
+
+/// A stored conversation, or an empty string if it doesn't exist.
+#[tauri::command]
+fn load_ai_conversation(conversation_id: &str) -> Result<String, String> {
+    read_json_file(&ai_conversation_path(conversation_id)?)
+}
+
+/// Summaries of all stored conversations, most recently updated first, as
+/// `[{ id, createdAt, updatedAt, title }]`.
+#[tauri::command]
+fn list_ai_conversations() -> Result<String, String> {
+    let mut index = read_ai_conversation_index()?;
+    index.sort_by_key(|c| std::cmp::Reverse(c.updated_at));
+    serde_json::to_string(&index).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_ai_conversation(conversation_id: &str) -> Result<(), String> {
+    let path = ai_conversation_path(conversation_id)?;
+    if let Err(e) = fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(format!("failed to delete {}: {}", path.display(), e));
+        }
+    }
+    let mut index = read_ai_conversation_index()?;
+    index.retain(|c| c.id != conversation_id);
+    write_ai_conversation_index(&index)
+}
+
+// ----------------- Editor state -----------------
+
+/// `editor_states/<sha256(file_id)>.json`; hashing keeps IDs with `:` or `/`
+/// out of the file name.
+fn editor_state_path(file_id: &str) -> Result<PathBuf, String> {
+    use sha2::{Digest, Sha256};
     let mut base = base_dir()?;
-    base.push("workspace_plugins");
+    base.push("editor_states");
     ensure_dir(&base)?;
-    base.push(format!("{}.json", vault_id));
-    write_json_file(&base, &json)
+    base.push(format!("{:x}.json", Sha256::digest(file_id.as_bytes())));
+    Ok(base)
 }
 
-// Remote installed plugin objects: stored as an array in remote_plugins.json
+/// Saved cursor / scroll state of a file (opaque JSON), or an empty string.
 #[tauri::command]
-fn get_installed_remote_plugins() -> Result<String, String> {
-    let mut base = base_dir()?;
-    base.push("remote_plugins.json");
-    read_json_file(&base)
+fn get_editor_state(file_id: &str) -> Result<String, String> {
+    read_json_file(&editor_state_path(file_id)?)
 }
 
 #[tauri::command]
-fn save_installed_remote_plugin(plugin_json: String) -> Result<(), String> {
-    // plugin_json is expected to be a JSON object with { id, code, manifestUrl }
+fn save_editor_state(file_id: &str, state_json: String) -> Result<(), String> {
+    write_json_file(&editor_state_path(file_id)?, &state_json)
+}
+
+#[tauri::command]
+fn clear_editor_state(file_id: &str) -> Result<(), String> {
+    match fs::remove_file(editor_state_path(file_id)?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+fn clear_all_editor_states() -> Result<(), String> {
     let mut base = base_dir()?;
-    base.push("remote_plugins.json");
-    ensure_dir(&base.parent().unwrap_or(Path::new("/")))?;
-    // read current
-    let current = read_json_file(&base)?;
-    let mut vec: Vec<serde_json::Value> = if current.trim().is_empty() {
-        vec![]
-    } else {
-        serde_json::from_str(&current).map_err(|e| format!("parse error: {}", e))?
-    };
-    let plugin_val: serde_json::Value =
-        serde_json::from_str(&plugin_json).map_err(|e| format!("invalid plugin json: {}", e))?;
-    // replace if exists by id, otherwise push
-    if let Some(id) = plugin_val.get("id").and_then(|v| v.as_str()) {
-        if let Some(pos) = vec
-            .iter()
-            .position(|p| p.get("id").and_then(|x| x.as_str()) == Some(id))
-        {
-            vec[pos] = plugin_val;
+    base.push("editor_states");
+    match fs::remove_dir_all(&base) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+// ----------------- Preferences -----------------
+
+/// Load preferences.json as a map (empty if the file is missing).
+fn read_preferences() -> Result<HashMap<String, String>, String> {
+    let mut base = base_dir()?;
+    base.push("preferences.json");
+    let raw = read_json_file(&base)?;
+    if raw.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+/// A `.bak` older than this is rotated to `.bak.old` before the next backup,
+/// so a day-old snapshot survives a burst of bad saves.
+const PREFERENCES_BACKUP_ROTATE_AGE: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+fn preferences_backup_paths() -> Result<(PathBuf, PathBuf, PathBuf), String> {
+    let base = base_dir()?;
+    Ok((
+        base.join("preferences.json"),
+        base.join("preferences.json.bak"),
+        base.join("preferences.json.bak.old"),
+    ))
+}
+
+/// Write the preferences map back to preferences.json, first copying the
+/// current file to `preferences.json.bak` (at most two backup levels).
+fn write_preferences(map: &HashMap<String, String>) -> Result<(), String> {
+    let (prefs, bak, bak_old) = preferences_backup_paths()?;
+    ensure_dir(prefs.parent().unwrap_or(Path::new("/")))?;
+    if prefs.exists() {
+        let bak_age = fs::metadata(&bak)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok());
+        if bak_age.map(|age| age > PREFERENCES_BACKUP_ROTATE_AGE).unwrap_or(false) {
+            fs::rename(&bak, &bak_old).map_err(|e| format!("failed to rotate {}: {}", bak.display(), e))?;
+        }
+        fs::copy(&prefs, &bak).map_err(|e| format!("failed to back up {}: {}", prefs.display(), e))?;
+    }
+    let s = serde_json::to_string_pretty(map).map_err(|e| e.to_string())?;
+    write_json_file(&prefs, &s)
+}
+
+/// Replace preferences.json with the most recent valid backup
+/// (`.bak`, then `.bak.old`).
+#[tauri::command]
+fn restore_preferences_from_backup() -> Result<(), String> {
+    let (prefs, bak, bak_old) = preferences_backup_paths()?;
+    for candidate in [&bak, &bak_old] {
+        let raw = read_json_file(candidate)?;
+        if raw.trim().is_empty() {
+            continue;
+        }
+        if serde_json::from_str::<HashMap<String, String>>(&raw).is_ok() {
+            return write_json_file(&prefs, &raw);
+        }
+    }
+    Err("no valid preferences backup found".to_string())
+}
+
+#[tauri::command]
+fn get_preference(key: &str) -> Result<String, String> {
+    let map = read_preferences()?;
+    Ok(map.get(key).cloned().unwrap_or_default())
+}
+
+#[tauri::command]
+fn save_preference(key: &str, value: &str) -> Result<(), String> {
+    let mut map = read_preferences()?;
+    map.insert(key.to_string(), value.to_string());
+    write_preferences(&map)
+}
+
+/// Return every preference as one JSON object (`{}` when none are set).
+#[tauri::command]
+fn get_all_preferences() -> Result<String, String> {
+    serde_json::to_string(&read_preferences()?).map_err(|e| e.to_string())
+}
+
+/// Merge a JSON object of preferences into preferences.json in one write.
+/// Keys not present in `json` keep their current values.
+#[tauri::command]
+fn save_preferences_bulk(json: String) -> Result<(), String> {
+    let updates: HashMap<String, String> = serde_json::from_str(&json).map_err(|e| format!("invalid preferences: {}", e))?;
+    let mut map = read_preferences()?;
+    map.extend(updates);
+    write_preferences(&map)
+}
+
+/// Copy the preferences to `dest_path` (absolute, inside the app data folder
+/// or a vault) for backup or transfer.
+#[tauri::command]
+fn export_preferences(dest_path: String) -> Result<(), String> {
+    let dest = Path::new(&dest_path);
+    if !dest.is_absolute() {
+        return Err(format!("expected an absolute path, got {}", dest_path));
+    }
+    check_command_path(dest)?;
+    let s = serde_json::to_string_pretty(&read_preferences()?).map_err(|e| e.to_string())?;
+    write_json_file(dest, &s)
+}
+
+/// Replace preferences.json with the file at `src_path` (inside the app data
+/// folder or a vault), which must hold a JSON object of string values. The
+/// current preferences are backed up first.
+#[tauri::command]
+fn import_preferences(src_path: String) -> Result<(), String> {
+    check_command_path(Path::new(&src_path))?;
+    let raw = read_json_file(Path::new(&src_path))?;
+    if raw.trim().is_empty() {
+        return Err(format!("{} is missing or empty", src_path));
+    }
+    let map: HashMap<String, String> =
+        serde_json::from_str(&raw).map_err(|e| format!("{} is not a valid preferences file: {}", src_path, e))?;
+    write_preferences(&map)
+}
+
+/// Remove a preference so it reads as unset again. Missing keys are not an error.
+#[tauri::command]
+fn delete_preference(key: &str) -> Result<(), String> {
+    let mut map = read_preferences()?;
+    if map.remove(key).is_some() {
+        write_preferences(&map)?;
+    }
+    Ok(())
+}
+
+/// Convert a preference key (`fontSize`, `font-size`, `font_size`) into an
+/// environment variable name (`FOCOSX_PREF_FONT_SIZE`).
+fn dotenv_key(key: &str) -> String {
+    let mut out = String::from("FOCOSX_PREF_");
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_uppercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
         } else {
-            vec.push(plugin_val);
+            if !out.ends_with('_') {
+                out.push('_');
+            }
+            prev_lower = false;
         }
-    } else {
-        return Err("plugin json must include an 'id' field".to_string());
     }
-    let s = serde_json::to_string_pretty(&vec).map_err(|e| e.to_string())?;
-    write_json_file(&base, &s)
+    out
 }
 
+/// Write preferences as a `.env` file (`FOCOSX_PREF_<KEY>=value`) so a
+/// configuration can be replicated in CI. `dest_path` must be absolute and
+/// inside the app data folder or a vault. JSON object values are skipped
+/// with a comment since they have no sensible env representation.
 #[tauri::command]
-fn remove_installed_remote_plugin(id: &str) -> Result<(), String> {
-    let mut base = base_dir()?;
-    base.push("remote_plugins.json");
-    let cur = read_json_file(&base)?;
-    if cur.trim().is_empty() {
-        return Ok(());
+fn export_preferences_as_dotenv(dest_path: String) -> Result<(), String> {
+    let dest = Path::new(&dest_path);
+    if !dest.is_absolute() {
+        return Err(format!("expected an absolute path, got {}", dest_path));
+    }
+    check_command_path(dest)?;
+    let map = read_preferences()?;
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+
+    let mut out = String::new();
+    for key in keys {
+        let value = &map[key];
+        let env_key = dotenv_key(key);
+        if let Ok(serde_json::Value::Object(_)) = serde_json::from_str::<serde_json::Value>(value) {
+            out.push_str(&format!("# {} skipped: JSON object value\n", env_key));
+            continue;
+        }
+        let needs_quotes = value.is_empty()
+            || value.chars().any(|c| c.is_whitespace() || matches!(c, '#' | '"' | '\'' | '\\' | '$'));
+        if needs_quotes {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+                .replace('$', "\\$");
+            out.push_str(&format!("{}=\"{}\"\n", env_key, escaped));
+        } else {
+            out.push_str(&format!("{}={}\n", env_key, value));
+        }
+    }
+    write_text_file(dest, &out).map(|_| ())
+}
+
+// ----------------- Caches -----------------
+
+/// True when a cache entry name belongs to `vault_id` (`<id>`, `<id>.json`, `<id>_…`),
+/// or always when no vault filter is given.
+fn cache_entry_matches(name: &str, vault_id: Option<&str>) -> bool {
+    match vault_id {
+        Some(id) => name
+            .strip_prefix(id)
+            .map(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('_'))
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+/// Cache folders under `base_dir()` with one entry per vault, flushed like
+/// `*_cache/` and `*_index/` folders.
+const CACHE_DIRS: &[&str] = &["sync_manifests"];
+
+/// Cache files inside a filesystem vault's `.focosx` folder.
+const VAULT_CACHE_FILES: &[&str] = &["backlinks.json"];
+
+/// Delete cache files below `dir`: everything inside `*_cache/`, `*_index/`
+/// and `CACHE_DIRS` folders plus any `*.idx` file.
+fn flush_cache_dir(dir: &Path, vault_id: Option<&str>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if is_dir && (name.ends_with("_cache") || name.ends_with("_index") || CACHE_DIRS.contains(&name.as_str())) {
+            for cached in fs::read_dir(&path).into_iter().flatten().flatten() {
+                if !cache_entry_matches(&cached.file_name().to_string_lossy(), vault_id) {
+                    continue;
+                }
+                let p = cached.path();
+                let _ = if p.is_dir() { fs::remove_dir_all(&p) } else { fs::remove_file(&p) };
+            }
+        } else if is_dir {
+            flush_cache_dir(&path, vault_id);
+        } else if name.ends_with(".idx") && cache_entry_matches(&name, vault_id) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Drop every on-disk cache / index under the app data directory and in
+/// filesystem vaults' `.focosx` folders (sync manifests, backlink indexes),
+/// and the in-memory caches and change log. With `vault_id` only that
+/// vault's entries are removed.
+#[tauri::command]
+fn flush_all_caches(
+    recent: tauri::State<'_, RecentFilesCache>,
+    changes: tauri::State<'_, VaultChangeLog>,
+    vault_id: Option<String>,
+) -> Result<(), String> {
+    let base = base_dir()?;
+    flush_cache_dir(&base, vault_id.as_deref());
+
+    let vault_roots: Vec<PathBuf> = match &vault_id {
+        Some(id) => find_vault_path(id)?.into_iter().collect(),
+        None => read_vaults()?
+            .iter()
+            .filter_map(|v| v.get("path").and_then(|x| x.as_str()))
+            .map(PathBuf::from)
+            .collect(),
+    };
+    for root in vault_roots.iter().filter(|p| p.is_absolute()) {
+        for file in VAULT_CACHE_FILES {
+            let _ = fs::remove_file(root.join(".focosx").join(file));
+        }
+    }
+
+    let mut recent = recent.0.lock().map_err(|e| e.to_string())?;
+    let mut changes = changes.0.lock().map_err(|e| e.to_string())?;
+    match &vault_id {
+        Some(id) => {
+            recent.remove(id);
+            changes.remove(id);
+        }
+        None => {
+            recent.clear();
+            changes.clear();
+        }
     }
-    let mut vec: Vec<serde_json::Value> =
-        serde_json::from_str(&cur).map_err(|e| format!("parse error: {}", e))?;
-    vec.retain(|p| p.get("id").and_then(|x| x.as_str()) != Some(id));
-    let s = serde_json::to_string_pretty(&vec).map_err(|e| e.to_string())?;
-    write_json_file(&base, &s)
+    Ok(())
 }
 
-// ----------------- AI Dock Config -----------------
+// ----------------- Delete Vault (cleanup) -----------------
 
-#[tauri::command]
-fn get_ai_dock_config() -> Result<String, String> {
-    let mut base = base_dir()?;
-    base.push("ai_dock.json");
-    read_json_file(&base)
-}
+/// Per-vault files under `base_dir()` removed by `delete_vault`, as
+/// `(directory, file_pattern)` where `{id}` in the pattern is the vault ID.
+const VAULT_CLEANUP_MANIFEST: &[(&str, &str)] = &[
+    ("trees", "{id}.json"),
+    ("workspace_plugins", "{id}.json"),
+    ("vault_ai_configs", "{id}.json"),
+    ("activity_log", "{id}.json"),
+    ("workspace_states", "{id}.json"),
+    ("sync_manifests", "{id}.json"),
+    ("configs", "{id}.json"),
+    ("trash", "{id}"),
+];
 
-#[tauri::command]
-fn save_ai_dock_config(json: String) -> Result<(), String> {
-    let mut base = base_dir()?;
-    base.push("ai_dock.json");
-    write_json_file(&base, &json)
-}
+/// JSON maps under `base_dir()` keyed by vault ID; `delete_vault` drops the vault's key.
+const VAULT_KEYED_MAPS: &[&str] = &["vault_icons.json", "vault_storage_limits.json"];
 
-// ----------------- Preferences -----------------
+/// Cleanup entries registered at runtime in addition to `VAULT_CLEANUP_MANIFEST`,
+/// so new per-vault storage can opt into `delete_vault` without editing it.
+#[derive(Default)]
+struct VaultCleanupRegistry(Mutex<Vec<(String, String)>>);
 
-#[tauri::command]
-fn get_preference(key: &str) -> Result<String, String> {
-    let mut base = base_dir()?;
-    base.push("preferences.json");
-    let raw = read_json_file(&base)?;
+/// Remove every manifest entry for `vault_id` (files or folders); missing entries are ignored.
+fn remove_vault_files(base: &Path, vault_id: &str, manifest: &[(&str, &str)]) {
+    for (dir, pattern) in manifest {
+        let path = base.join(dir).join(pattern.replace("{id}", vault_id));
+        if path.is_dir() {
+            let _ = fs::remove_dir_all(&path);
+        } else {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Remove the `vault_id` key from a JSON object file, if present.
+fn remove_vault_key(path: &Path, vault_id: &str) -> Result<(), String> {
+    let raw = read_json_file(path)?;
     if raw.trim().is_empty() {
-        return Ok(String::new());
+        return Ok(());
     }
-    let map: HashMap<String, String> = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
-    Ok(map.get(key).cloned().unwrap_or_default())
+    let mut map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    if map.remove(vault_id).is_some() {
+        let s = serde_json::to_string_pretty(&map).map_err(|e| e.to_string())?;
+        write_json_file(path, &s)?;
+    }
+    Ok(())
 }
 
+/// Register an extra `(directory, file_pattern)` that `delete_vault` should clean up.
+/// Both must be relative paths without `..`, so cleanup stays inside `base_dir()`.
 #[tauri::command]
-fn save_preference(key: &str, value: &str) -> Result<(), String> {
-    let mut base = base_dir()?;
-    base.push("preferences.json");
-    ensure_dir(&base.parent().unwrap_or(Path::new("/")))?;
-    let raw = read_json_file(&base)?;
-    let mut map: HashMap<String, String> = if raw.trim().is_empty() {
-        HashMap::new()
-    } else {
-        serde_json::from_str(&raw).map_err(|e| e.to_string())?
-    };
-    map.insert(key.to_string(), value.to_string());
-    let s = serde_json::to_string_pretty(&map).map_err(|e| e.to_string())?;
-    write_json_file(&base, &s)
+fn register_vault_cleanup_path(registry: tauri::State<'_, VaultCleanupRegistry>, directory: String, file_pattern: String) -> Result<(), String> {
+    if !file_pattern.contains("{id}") {
+        return Err("file_pattern must contain {id}".to_string());
+    }
+    for part in [&directory, &file_pattern] {
+        let relative = Path::new(part.as_str())
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir));
+        if !relative {
+            return Err(format!("path not allowed: {}", part));
+        }
+    }
+    let mut entries = registry.0.lock().map_err(|e| e.to_string())?;
+    if !entries.iter().any(|(d, p)| *d == directory && *p == file_pattern) {
+        entries.push((directory, file_pattern));
+    }
+    Ok(())
 }
 
-// ----------------- Delete Vault (cleanup) -----------------
-
+/// Remove a vault: its per-vault app data, the `.focosx` folder inside a
+/// filesystem vault and its `vaults.json` entry. The `.focosx` folder holds
+/// the vault's contents, tree and config but also its trash and file version
+/// history, which are gone for good afterwards. The user's own files in the
+/// vault folder are left alone. A vault whose `.focosx` folder is the app
+/// data dir itself (a vault at `$HOME` on Linux) is refused.
 #[tauri::command]
-fn delete_vault(vault_id: &str) -> Result<(), String> {
-    let mut base = base_dir()?;
-    // remove tree file
-    base.push("trees");
-    let mut tree_path = base.clone();
-    tree_path.push(format!("{}.json", vault_id));
-    let _ = fs::remove_file(&tree_path);
-    // remove workspace plugins
-    let mut wp = base;
-    if wp.ends_with("trees") {
-        // replace segment "trees" with "workspace_plugins"
-        wp.pop();
-    }
-    wp.push("workspace_plugins");
-    let mut wp_path = wp.clone();
-    wp_path.push(format!("{}.json", vault_id));
-    let _ = fs::remove_file(&wp_path);
+fn delete_vault(registry: tauri::State<'_, VaultCleanupRegistry>, vault_id: &str) -> Result<(), String> {
+    let base = base_dir()?;
+    if let Some(root) = find_vault_path(vault_id)?.filter(|p| p.is_absolute() && p.exists()) {
+        let meta_dir = root.join(".focosx");
+        if meta_dir.canonicalize().ok() == base.canonicalize().ok() && meta_dir.exists() {
+            return Err(format!(
+                "Refusing to delete vault {}: its metadata folder {} is the app data folder",
+                vault_id,
+                meta_dir.display()
+            ));
+        }
+        if meta_dir.is_dir() {
+            fs::remove_dir_all(&meta_dir).map_err(|e| format!("failed to remove {}: {}", meta_dir.display(), e))?;
+        }
+    }
+    remove_vault_files(&base, vault_id, VAULT_CLEANUP_MANIFEST);
+    {
+        let registered = registry.0.lock().map_err(|e| e.to_string())?;
+        let manifest: Vec<(&str, &str)> = registered.iter().map(|(d, p)| (d.as_str(), p.as_str())).collect();
+        remove_vault_files(&base, vault_id, &manifest);
+    }
+    for map_file in VAULT_KEYED_MAPS {
+        remove_vault_key(&base.join(map_file), vault_id)?;
+    }
 
+    let mut vaults = read_vaults()?;
+    let before = vaults.len();
+    vaults.retain(|v| v.get("id").and_then(|x| x.as_str()) != Some(vault_id));
+    if vaults.len() != before {
+        write_vaults(&vaults)?;
+    }
     Ok(())
 }
 
 // ----------------- Generic filesystem utilities exposed -----------------
 
-/// Read an arbitrary file (absolute or relative) and return its text contents.
-/// This is a thin wrapper around the internal `read_text_file` helper.
+/// Read a file (absolute or relative) inside a vault or the app data dir and
+/// return its text contents. This is a thin wrapper around the internal
+/// `read_text_file` helper.
 #[tauri::command]
 fn read_text_file_cmd(path: String) -> Result<String, String> {
     let p = Path::new(&path);
+    check_command_path(p)?;
     read_text_file(p)
 }
 
-/// Write text to an arbitrary file path (absolute or relative). Ensures the
-/// parent directory exists before writing.
+/// Write text to a file path (absolute or relative) inside a vault or the app
+/// data dir. Ensures the parent directory exists before writing.
 #[tauri::command]
 fn write_text_file_cmd(path: String, content: String) -> Result<(), String> {
     let p = Path::new(&path);
-    write_text_file(p, &content)
+    check_command_path(p)?;
+    write_text_file(p, &content).map(|_| ())
 }
 
 /// Create a directory (and parents) at the provided path.
 #[tauri::command]
 fn create_dir_cmd(path: String) -> Result<(), String> {
+    check_command_path(Path::new(&path))?;
     ensure_dir(Path::new(&path))
 }
 
-/// List directory contents for a given path.
-#[tauri::command]
-fn list_dir_cmd(path: String) -> Result<Vec<String>, String> {
+/// Entries of a directory with their metadata, sorted by `sort_by`: one of
+/// `"name"` (case-insensitive, the default), `"modified"` or `"size"`; all
+/// orders are ascending. Sorting happens here because `read_dir` order
+/// differs between platforms.
+fn read_dir_sorted(path: &str, sort_by: Option<String>) -> Result<Vec<(PathBuf, Option<fs::Metadata>)>, String> {
+    check_command_path(Path::new(path))?;
     let rd = fs::read_dir(path).map_err(|e| e.to_string())?;
     let mut v = Vec::new();
     for e in rd {
         let e = e.map_err(|e| e.to_string())?;
-        v.push(e.path().to_string_lossy().to_string());
+        let meta = e.metadata().ok();
+        v.push((e.path(), meta));
+    }
+    match sort_by.as_deref().unwrap_or("name") {
+        "name" => v.sort_by_key(|(p, _)| {
+            p.file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        }),
+        "modified" => v.sort_by_key(|(_, m)| m.as_ref().and_then(|m| m.modified().ok())),
+        "size" => v.sort_by_key(|(_, m)| m.as_ref().map(|m| m.len()).unwrap_or(0)),
+        other => return Err(format!("unknown sort_by: {}", other)),
     }
     Ok(v)
 }
 
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DirEntryInfo {
+    path: String,
+    name: String,
+    is_dir: bool,
+    size_bytes: Option<u64>,
+    modified_ms: Option<i64>,
+}
+
+/// List directory contents for a given path as
+/// `[{ path, name, isDir, sizeBytes, modifiedMs }]` (`sizeBytes` is null for
+/// folders). See `read_dir_sorted` for `sort_by`.
+#[tauri::command]
+fn list_dir_cmd(path: String, sort_by: Option<String>) -> Result<Vec<DirEntryInfo>, String> {
+    Ok(read_dir_sorted(&path, sort_by)?
+        .into_iter()
+        .map(|(p, meta)| {
+            let is_dir = meta.as_ref().map(|m| m.is_dir()).unwrap_or(false);
+            DirEntryInfo {
+                name: p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                path: p.to_string_lossy().to_string(),
+                is_dir,
+                size_bytes: meta.as_ref().filter(|_| !is_dir).map(|m| m.len()),
+                modified_ms: meta.as_ref().and_then(modified_ms),
+            }
+        })
+        .collect())
+}
+
+/// Paths of a directory's entries only; the format `list_dir_cmd` returned
+/// before it carried metadata.
+#[tauri::command]
+fn list_dir_paths_cmd(path: String, sort_by: Option<String>) -> Result<Vec<String>, String> {
+    Ok(read_dir_sorted(&path, sort_by)?
+        .into_iter()
+        .map(|(p, _)| p.to_string_lossy().to_string())
+        .collect())
+}
+
 /// Remove a file or directory (recursively) at the given path.
 #[tauri::command]
 fn remove_path_cmd(path: String) -> Result<(), String> {
     let p = Path::new(&path);
+    check_command_path(p)?;
     if !p.exists() {
         return Ok(());
     }
@@ -699,6 +4850,17 @@ fn remove_path_cmd(path: String) -> Result<(), String> {
     }
 }
 
+/// Reveal a file or folder in the OS file manager (Finder / Explorer / the
+/// desktop's file manager on Linux), selecting the item where supported.
+#[tauri::command]
+fn open_path_in_file_manager(path: String) -> Result<(), String> {
+    let p = Path::new(&path);
+    if !p.exists() {
+        return Err(format!("path does not exist: {}", path));
+    }
+    tauri_plugin_opener::reveal_item_in_dir(p).map_err(|e| format!("failed to reveal {}: {}", path, e))
+}
+
 /// --- New helpers for vaults stored as real filesystem folders ---
 /// These commands allow the frontend to explicitly read/write tree and file
 /// content directly inside a user-specified vault folder (absolute path).
@@ -706,47 +4868,75 @@ fn remove_path_cmd(path: String) -> Result<(), String> {
 
 /// Save a tree.json into the given vault folder under `.focosx/tree.json`.
 /// Creates `.focosx` when necessary.
+///
+/// Deprecated: use `save_tree` with the vault ID, which picks the vault
+/// folder or the app-managed trees folder itself.
 #[tauri::command]
 fn save_tree_to_vault_path(vault_folder: String, json: String) -> Result<(), String> {
-    let mut dir = PathBuf::from(&vault_folder);
-    // Ensure .focosx directory exists
-    dir.push(".focosx");
-    ensure_dir(&dir)?;
-    dir.push("tree.json");
-    write_json_file(&dir, &json)
+    let path = vault_folder_tree_file(Path::new(&vault_folder));
+    check_command_path(&path)?;
+    if let Some(parent) = path.parent() {
+        ensure_dir(parent)?;
+    }
+    write_json_file(&path, &json)
 }
 
 /// Load the `.focosx/tree.json` file from a vault folder. If the file doesn't
 /// exist this returns an empty string (frontend will treat as missing/empty).
+///
+/// Deprecated: use `load_tree` with the vault ID, which scans the vault
+/// folder or reads the app-managed tree as appropriate.
 #[tauri::command]
 fn load_tree_from_vault_path(vault_folder: String) -> Result<String, String> {
-    let mut dir = PathBuf::from(&vault_folder);
-    dir.push(".focosx");
-    dir.push("tree.json");
-    read_json_file(&dir)
+    let path = vault_folder_tree_file(Path::new(&vault_folder));
+    check_command_path(&path)?;
+    read_json_file(&path)
 }
 
-/// Save arbitrary file content into an absolute path inside the vault (or anywhere).
+/// Save arbitrary file content into an absolute path inside a vault or the app data dir.
 /// The `path` should be the full absolute file path to write (for example:
 /// /home/user/MyVault/.focosx/contents/<fileId>.json or /home/user/MyVault/Notes/foo.md)
 #[tauri::command]
 fn save_file_to_absolute_path(path: String, json: String) -> Result<(), String> {
     let p = Path::new(&path);
+    check_command_path(p)?;
     if let Some(parent) = p.parent() {
         ensure_dir(parent)?;
     }
-    write_text_file(p, &json)
+    write_text_file(p, &json).map(|_| ())
 }
 
 /// Load arbitrary file content from an absolute path.
 #[tauri::command]
 fn load_file_from_absolute_path(path: String) -> Result<String, String> {
     let p = Path::new(&path);
+    check_command_path(p)?;
     read_text_file(p)
 }
 
+/// Emit a node mutation event (`node-created`, `node-deleted`, `node-renamed`,
+/// `node-moved`, `node-restored`) so the file tree and sidebars can update
+/// without reloading the tree. Also records the change for the payload's `vault_id`.
+fn emit_node_event(app: &tauri::AppHandle, event: &str, payload: serde_json::Value) {
+    if let Some(vault_id) = payload.get("vault_id").and_then(|v| v.as_str()) {
+        note_vault_change(app, vault_id);
+    }
+    if let Err(e) = app.emit(event, payload) {
+        eprintln!("[{}] failed to emit event: {}", event, e);
+    }
+}
+
+/// Create a file or folder and return its ID. Files are written with
+/// `initial_content` (atomically) when given, and empty otherwise.
 #[tauri::command]
-fn create_node_cmd(vault_id: &str, parent_id: Option<String>, name: &str, node_type: &str) -> Result<String, String> {
+fn create_node_cmd(
+    app: tauri::AppHandle,
+    vault_id: &str,
+    parent_id: Option<String>,
+    name: &str,
+    node_type: &str,
+    initial_content: Option<String>,
+) -> Result<String, String> {
     eprintln!("[create_node_cmd] vault_id={} parent_id={:?} name={} node_type={}", vault_id, parent_id, name, node_type);
     
     let mut base = base_dir()?;
@@ -776,7 +4966,7 @@ fn create_node_cmd(vault_id: &str, parent_id: Option<String>, name: &str, node_t
     }
 
     let mut target_path = root.clone();
-    if let Some(pid) = parent_id {
+    if let Some(pid) = &parent_id {
         if let Some((_, path)) = pid.split_once(':') {
             target_path.push(path);
         } else {
@@ -784,8 +4974,20 @@ fn create_node_cmd(vault_id: &str, parent_id: Option<String>, name: &str, node_t
         }
     }
     
-    target_path.push(name);
+    // Extensionless notes get the extension of the default note format so the
+    // OS and other tools can identify them.
+    if node_type == "FILE" && Path::new(name).extension().is_none() {
+        let format = read_preferences()?.get("default_note_format").cloned().unwrap_or_default();
+        match format.as_str() {
+            "markdown" => target_path.push(format!("{}.md", name)),
+            "text" => target_path.push(format!("{}.txt", name)),
+            _ => target_path.push(name),
+        }
+    } else {
+        target_path.push(name);
+    }
     eprintln!("[create_node_cmd] target_path={:?}", target_path);
+    ensure_path_within(&root, &target_path)?;
 
     if node_type == "FOLDER" {
         ensure_dir(&target_path)?;
@@ -794,8 +4996,13 @@ fn create_node_cmd(vault_id: &str, parent_id: Option<String>, name: &str, node_t
         if let Some(parent) = target_path.parent() {
             ensure_dir(parent)?;
         }
-        // Create empty file
-        fs::write(&target_path, "").map_err(|e| e.to_string())?;
+        match &initial_content {
+            Some(content) => {
+                write_text_file(&target_path, content)?;
+            }
+            // Create empty file
+            None => fs::write(&target_path, "").map_err(|e| e.to_string())?,
+        }
         eprintln!("[create_node_cmd] Created file");
     }
 
@@ -803,46 +5010,72 @@ fn create_node_cmd(vault_id: &str, parent_id: Option<String>, name: &str, node_t
     let raw_id = relative_path.to_string_lossy().to_string().replace("\\", "/");
     let result = format!("{}:{}", vault_id, raw_id);
     eprintln!("[create_node_cmd] Returning: {}", result);
+    emit_node_event(
+        &app,
+        "node-created",
+        json!({
+            "vault_id": vault_id,
+            "file_id": result,
+            "node_type": node_type,
+            "parent_id": parent_id,
+            "name": target_path.file_name().map(|n| n.to_string_lossy().to_string()),
+        }),
+    );
     Ok(result)
 }
 
 #[tauri::command]
-fn delete_node_cmd(vault_id: &str, id: &str) -> Result<(), String> {
-    let mut base = base_dir()?;
-    base.push("vaults.json");
-    let vraw = read_json_file(&base)?;
-    let vs: serde_json::Value = serde_json::from_str(&vraw).map_err(|e| e.to_string())?;
-    
-    let mut vault_path = None;
-    if let Some(arr) = vs.as_array() {
-        for v in arr {
-            if v.get("id").and_then(|x| x.as_str()) == Some(vault_id) {
-                if let Some(p) = v.get("path").and_then(|x| x.as_str()) {
-                    vault_path = Some(PathBuf::from(p));
-                }
-            }
-        }
-    }
+fn delete_node_cmd(app: tauri::AppHandle, vault_id: &str, id: &str, permanent: Option<bool>) -> Result<(), String> {
+    let root = find_vault_path(vault_id)?.ok_or("Vault not found or has no path")?;
+    let target_path = node_disk_path(&root, id);
+    ensure_path_within(&root, &target_path)?;
 
-    let root = vault_path.ok_or("Vault not found or has no path")?;
-    let mut target_path = root.clone();
-    
-    if let Some((_, path)) = id.split_once(':') {
-        target_path.push(path);
+    if permanent.unwrap_or(false) {
+        let res = if target_path.is_dir() { fs::remove_dir_all(&target_path) } else { fs::remove_file(&target_path) };
+        res.map_err(|e| format!("failed to delete {}: {}", target_path.display(), e))?;
     } else {
-        target_path.push(id);
+        move_to_trash(vault_id, id, &target_path)?;
     }
+    emit_node_event(&app, "node-deleted", json!({ "vault_id": vault_id, "file_id": id }));
+    Ok(())
+}
 
-    if target_path.is_dir() {
-        fs::remove_dir_all(target_path).map_err(|e| e.to_string())?;
-    } else {
-        fs::remove_file(target_path).map_err(|e| e.to_string())?;
+#[derive(serde::Serialize)]
+struct BatchDeleteFailure {
+    id: String,
+    error: String,
+}
+
+#[derive(serde::Serialize)]
+struct BatchDeleteResult {
+    deleted: Vec<String>,
+    failed: Vec<BatchDeleteFailure>,
+}
+
+/// Delete several nodes with a single vaults.json lookup. Failures are
+/// collected per ID and never abort the rest of the batch.
+#[tauri::command]
+fn batch_delete_nodes(app: tauri::AppHandle, vault_id: &str, ids: Vec<String>) -> Result<String, String> {
+    let root = find_vault_path(vault_id)?.ok_or("Vault not found or has no path")?;
+    let mut result = BatchDeleteResult {
+        deleted: Vec::new(),
+        failed: Vec::new(),
+    };
+    for id in ids {
+        let path = node_disk_path(&root, &id);
+        match ensure_path_within(&root, &path).and_then(|()| move_to_trash(vault_id, &id, &path)) {
+            Ok(()) => {
+                emit_node_event(&app, "node-deleted", json!({ "vault_id": vault_id, "file_id": id }));
+                result.deleted.push(id)
+            }
+            Err(error) => result.failed.push(BatchDeleteFailure { id, error }),
+        }
     }
-    Ok(())
+    serde_json::to_string(&result).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn rename_node_cmd(vault_id: &str, id: &str, new_name: &str) -> Result<String, String> {
+fn rename_node_cmd(app: tauri::AppHandle, vault_id: &str, id: &str, new_name: &str) -> Result<String, String> {
     let mut base = base_dir()?;
     base.push("vaults.json");
     let vraw = read_json_file(&base)?;
@@ -870,14 +5103,562 @@ fn rename_node_cmd(vault_id: &str, id: &str, new_name: &str) -> Result<String, S
 
     let mut new_path = old_path.parent().ok_or("Invalid path")?.to_path_buf();
     new_path.push(new_name);
+    ensure_path_within(&root, &old_path)?;
+    ensure_path_within(&root, &new_path)?;
 
     fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
 
     let relative_path = new_path.strip_prefix(&root).map_err(|e| e.to_string())?;
     let raw_id = relative_path.to_string_lossy().to_string().replace("\\", "/");
+    let new_id = format!("{}:{}", vault_id, raw_id);
+    emit_node_event(
+        &app,
+        "node-renamed",
+        json!({ "vault_id": vault_id, "old_file_id": id, "file_id": new_id, "name": new_name }),
+    );
+    Ok(new_id)
+}
+
+/// Rewrite links to a renamed note in one Markdown document. `wiki_targets`
+/// maps lowercased old wikilink targets (path or name, with or without
+/// extension) to their replacement; Markdown links whose last path segment is
+/// the old name (with or without extension) get the new one. A candidate is
+/// only rewritten when `points_to_renamed(is_wiki, target)` confirms it
+/// resolves to the renamed note, so links to another note of the same name
+/// are left alone. Heading anchors and aliases are kept; fenced code is left
+/// alone. Returns the new text and the number of links changed.
+fn rewrite_renamed_links(
+    content: &str,
+    wiki_targets: &HashMap<String, String>,
+    old_name: &str,
+    new_name: &str,
+    points_to_renamed: &mut dyn FnMut(bool, &str) -> bool,
+) -> (String, usize) {
+    let stem = |s: &str| s.rsplit_once('.').map(|(a, _)| a.to_string()).unwrap_or_else(|| s.to_string());
+    let (old_stem, new_stem) = (stem(old_name), stem(new_name));
+    let mut out = String::with_capacity(content.len());
+    let mut count = 0;
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.trim_start().starts_with("```") {
+            out.push_str(line);
+            continue;
+        }
+
+        // [[target#heading|alias]]
+        let mut wiki = String::with_capacity(line.len());
+        let mut rest = line;
+        while let Some(open) = rest.find("[[") {
+            wiki.push_str(&rest[..open + 2]);
+            rest = &rest[open + 2..];
+            let Some(close) = rest.find("]]") else { break };
+            let body = &rest[..close];
+            let (target, suffix) = body.split_at(body.find(['#', '|']).unwrap_or(body.len()));
+            let new_target = wiki_targets
+                .get(&target.trim().replace('\\', "/").to_lowercase())
+                .filter(|_| points_to_renamed(true, target.trim()));
+            match new_target {
+                Some(new_target) => {
+                    wiki.push_str(new_target);
+                    wiki.push_str(suffix);
+                    count += 1;
+                }
+                None => wiki.push_str(body),
+            }
+            wiki.push_str("]]");
+            rest = &rest[close + 2..];
+        }
+        wiki.push_str(rest);
+
+        // [text](dir/target.md#heading "title")
+        let mut rest = wiki.as_str();
+        while let Some(pos) = rest.find("](") {
+            out.push_str(&rest[..pos + 2]);
+            rest = &rest[pos + 2..];
+            let Some(end) = rest.find(')') else { break };
+            let inner = &rest[..end];
+            let url_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+            let url = &inner[..url_end];
+            let (path, anchor) = url.split_at(url.find('#').unwrap_or(url.len()));
+            let (dir, file) = path.split_at(path.rfind('/').map(|i| i + 1).unwrap_or(0));
+            let decoded = percent_decode(file);
+            let replacement = if decoded == old_name {
+                Some(new_name)
+            } else if decoded == old_stem {
+                Some(new_stem.as_str())
+            } else {
+                None
+            };
+            let replacement = replacement.filter(|_| !is_external_link(url) && points_to_renamed(false, path));
+            if let Some(replacement) = replacement {
+                out.push_str(dir);
+                out.push_str(&replacement.replace(' ', "%20"));
+                out.push_str(anchor);
+                out.push_str(&inner[url_end..]);
+                count += 1;
+            } else {
+                out.push_str(inner);
+            }
+            out.push(')');
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+    }
+    (out, count)
+}
+
+/// Rename a note like `rename_node_cmd`, then rewrite wikilinks and Markdown
+/// links to it in every Markdown file of the vault. Returns
+/// `{ renamedNodeId, updatedLinkCount, failed }`; renaming a folder updates
+/// no links. Once the rename is done, files that cannot be read or rewritten
+/// are listed in `failed` as `{ id, error }` and the rest are still updated.
+#[tauri::command]
+fn rename_node_and_update_links(app: tauri::AppHandle, vault_id: &str, id: &str, new_name: &str) -> Result<String, String> {
+    let root = vault_root(vault_id)?;
+    let is_file = node_disk_path(&root, id).is_file();
+    // Links are resolved against the vault as it was before the rename
+    let resolver = if is_file { Some(LinkResolver::new(vault_id)) } else { None };
+    let new_id = rename_node_cmd(app.clone(), vault_id, id, new_name)?;
+    let mut updated = 0;
+    let mut failed = Vec::new();
+    match resolver {
+        Some(Err(error)) => failed.push(json!({ "id": vault_id, "error": error })),
+        Some(Ok(mut resolver)) => {
+            let rel = |node: &str| node.split_once(':').map(|(_, r)| r.to_string()).unwrap_or_else(|| node.to_string());
+            let stem = |s: &str| s.rsplit_once('.').map(|(a, _)| a.to_string()).unwrap_or_else(|| s.to_string());
+            let (old_rel, new_rel) = (rel(id), rel(&new_id));
+            let old_name = old_rel.rsplit('/').next().unwrap_or(&old_rel).to_string();
+            let new_name = new_rel.rsplit('/').next().unwrap_or(&new_rel).to_string();
+            let mut wiki_targets = HashMap::new();
+            for (old, new) in [
+                (stem(&old_rel), stem(&new_rel)),
+                (old_rel.clone(), new_rel.clone()),
+                (stem(&old_name), stem(&new_name)),
+                (old_name.clone(), new_name.clone()),
+            ] {
+                wiki_targets.insert(old.to_lowercase(), new);
+            }
+
+            let notes: Vec<String> = resolver.files.iter().filter(|f| f.name.ends_with(".md")).map(|f| f.id.clone()).collect();
+            for source_id in notes {
+                // the renamed note itself now lives under its new ID (same folder)
+                let file_id = if source_id == id { new_id.clone() } else { source_id.clone() };
+                let mut points_to_renamed = |wiki: bool, target: &str| {
+                    let resolved = if wiki {
+                        resolver.resolve_wiki(target)
+                    } else {
+                        resolver.resolve_markdown(target, &source_id)
+                    };
+                    resolved.as_deref() == Some(id)
+                };
+                let result = read_text_file(&node_disk_path(&root, &file_id)).and_then(|content| {
+                    let (rewritten, count) = rewrite_renamed_links(&content, &wiki_targets, &old_name, &new_name, &mut points_to_renamed);
+                    if count > 0 {
+                        save_file_content(app.clone(), &file_id, rewritten)?;
+                    }
+                    Ok(count)
+                });
+                match result {
+                    Ok(count) => updated += count,
+                    Err(error) => failed.push(json!({ "id": file_id, "error": error })),
+                }
+            }
+        }
+        None => {}
+    }
+    Ok(json!({ "renamedNodeId": new_id, "updatedLinkCount": updated, "failed": failed }).to_string())
+}
+
+/// Node ID (`vaultId:relative/path`) of a path inside `root`.
+fn node_id_for_path(vault_id: &str, root: &Path, path: &Path) -> Result<String, String> {
+    let relative_path = path.strip_prefix(root).map_err(|e| e.to_string())?;
+    let raw_id = relative_path.to_string_lossy().to_string().replace("\\", "/");
     Ok(format!("{}:{}", vault_id, raw_id))
 }
 
+/// Move a file or folder to another folder of the same vault (`new_parent_id`
+/// `None` = vault root) and return its new ID. Moving a folder into itself or
+/// one of its descendants is refused.
+#[tauri::command]
+fn move_node_cmd(app: tauri::AppHandle, vault_id: &str, id: &str, new_parent_id: Option<String>) -> Result<String, String> {
+    let root = find_vault_path(vault_id)?.ok_or("Vault not found or has no path")?;
+    let source = node_disk_path(&root, id);
+    let dest_dir = match &new_parent_id {
+        Some(pid) => node_disk_path(&root, pid),
+        None => root.clone(),
+    };
+    ensure_path_within(&root, &source)?;
+    ensure_path_within(&root, &dest_dir)?;
+    if !source.exists() {
+        return Err(format!("{} does not exist", id));
+    }
+    if !dest_dir.is_dir() {
+        return Err("Destination is not a folder".to_string());
+    }
+    // Compare resolved paths so a destination reached through a symlink is
+    // caught too; the source itself is not resolved, since moving a symlink
+    // only moves the link.
+    let source_parent = canonicalize_lenient(source.parent().unwrap_or(&root))?;
+    let resolved_source = source.file_name().map(|n| source_parent.join(n)).unwrap_or(source_parent);
+    if canonicalize_lenient(&dest_dir)?.starts_with(&resolved_source) {
+        return Err("cannot move a folder into itself or its descendant".to_string());
+    }
+
+    let dest = dest_dir.join(source.file_name().ok_or("Invalid path")?);
+    if dest.exists() {
+        return Err(format!("{} already exists in the destination folder", dest.file_name().unwrap_or_default().to_string_lossy()));
+    }
+    fs::rename(&source, &dest).map_err(|e| e.to_string())?;
+
+    let new_id = node_id_for_path(vault_id, &root, &dest)?;
+    emit_node_event(
+        &app,
+        "node-moved",
+        json!({ "vault_id": vault_id, "old_file_id": id, "file_id": new_id, "parent_id": new_parent_id }),
+    );
+    Ok(new_id)
+}
+
+/// Recreate the symlink `src` at `dest`, pointing at the same target.
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> Result<(), String> {
+    let target = fs::read_link(src).map_err(|e| format!("failed to read link {}: {}", src.display(), e))?;
+    std::os::unix::fs::symlink(&target, dest).map_err(|e| format!("failed to copy link {}: {}", src.display(), e))
+}
+
+/// Recreate the symlink `src` at `dest`, pointing at the same target.
+#[cfg(windows)]
+fn copy_symlink(src: &Path, dest: &Path) -> Result<(), String> {
+    let target = fs::read_link(src).map_err(|e| format!("failed to read link {}: {}", src.display(), e))?;
+    let res = if fs::metadata(src).map(|m| m.is_dir()).unwrap_or(false) {
+        std::os::windows::fs::symlink_dir(&target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(&target, dest)
+    };
+    res.map_err(|e| format!("failed to copy link {}: {}", src.display(), e))
+}
+
+/// Copy a file, or a folder with everything below it, to `dest`. Symlinks
+/// are recreated as links rather than followed, so a link to a folder outside
+/// the vault (or to a parent) doesn't pull that whole tree into the copy.
+fn copy_recursive(src: &Path, dest: &Path) -> Result<(), String> {
+    let meta = fs::symlink_metadata(src).map_err(|e| format!("failed to read {}: {}", src.display(), e))?;
+    if meta.file_type().is_symlink() {
+        copy_symlink(src, dest)
+    } else if meta.is_dir() {
+        ensure_dir(dest)?;
+        for entry in fs::read_dir(src).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            copy_recursive(&entry.path(), &dest.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(src, dest)
+            .map(|_| ())
+            .map_err(|e| format!("failed to copy {}: {}", src.display(), e))
+    }
+}
+
+/// Duplicate a file or folder into `new_parent_id` (`None` = vault root) under
+/// `new_name` and return the copy's ID. Fails if `new_name` is already taken.
+#[tauri::command]
+fn copy_node_cmd(
+    app: tauri::AppHandle,
+    vault_id: &str,
+    id: &str,
+    new_parent_id: Option<String>,
+    new_name: &str,
+) -> Result<String, String> {
+    let root = find_vault_path(vault_id)?.ok_or("Vault not found or has no path")?;
+    let source = node_disk_path(&root, id);
+    let dest_dir = match &new_parent_id {
+        Some(pid) => node_disk_path(&root, pid),
+        None => root.clone(),
+    };
+    let dest = dest_dir.join(new_name);
+    ensure_path_within(&root, &source)?;
+    ensure_path_within(&root, &dest)?;
+    if !source.exists() {
+        return Err(format!("{} does not exist", id));
+    }
+    if !dest_dir.is_dir() {
+        return Err("Destination is not a folder".to_string());
+    }
+    if dest.exists() {
+        return Err(format!("{} already exists in the destination folder", new_name));
+    }
+    // Like move_node_cmd: a symlink source is copied as a link, so only its
+    // parent is resolved.
+    let source_parent = canonicalize_lenient(source.parent().unwrap_or(&root))?;
+    let resolved_source = source.file_name().map(|n| source_parent.join(n)).unwrap_or(source_parent);
+    if canonicalize_lenient(&dest)?.starts_with(&resolved_source) {
+        return Err("Cannot copy a folder into itself or one of its subfolders".to_string());
+    }
+    copy_recursive(&source, &dest)?;
+
+    let new_id = node_id_for_path(vault_id, &root, &dest)?;
+    emit_node_event(
+        &app,
+        "node-created",
+        json!({
+            "vault_id": vault_id,
+            "file_id": new_id,
+            "node_type": if dest.is_dir() { "FOLDER" } else { "FILE" },
+            "parent_id": new_parent_id,
+            "name": new_name,
+        }),
+    );
+    Ok(new_id)
+}
+
+// ----------------- Vault watcher -----------------
+
+/// Running filesystem watchers by vault ID; dropping a watcher stops it.
+#[derive(Default)]
+struct VaultWatchers(Mutex<HashMap<String, notify::RecommendedWatcher>>);
+
+/// `vault-changed` kind of a watcher event, or `None` for events the
+/// frontend doesn't care about (access, metadata-only, ...).
+fn watch_event_kind(kind: &notify::EventKind) -> Option<&'static str> {
+    use notify::event::{EventKind, ModifyKind};
+    match kind {
+        EventKind::Create(_) => Some("create"),
+        EventKind::Modify(ModifyKind::Name(_)) => Some("rename"),
+        EventKind::Modify(ModifyKind::Metadata(_)) => None,
+        EventKind::Modify(_) => Some("modify"),
+        EventKind::Remove(_) => Some("delete"),
+        _ => None,
+    }
+}
+
+/// Watch a filesystem vault for changes made outside the app and emit
+/// `vault-changed` with `{ vaultId, kind, path }` for each changed path
+/// (`kind` is `create`, `modify`, `delete` or `rename`). Changes below
+/// `.focosx` are not reported. Watching an already watched vault is a no-op.
+#[tauri::command]
+fn watch_vault(app: tauri::AppHandle, watchers: tauri::State<'_, VaultWatchers>, vault_id: &str) -> Result<(), String> {
+    use notify::Watcher;
+    let root = vault_root(vault_id)?;
+    let mut map = watchers.0.lock().map_err(|e| e.to_string())?;
+    if map.contains_key(vault_id) {
+        return Ok(());
+    }
+
+    let metadata_dir = root.join(".focosx");
+    let id = vault_id.to_string();
+    let handler = move |res: notify::Result<notify::Event>| {
+        let event = match res {
+            Ok(e) => e,
+            Err(e) => {
+                eprintln!("[watch_vault] {}: {}", id, e);
+                return;
+            }
+        };
+        let Some(kind) = watch_event_kind(&event.kind) else { return };
+        let paths: Vec<&PathBuf> = event.paths.iter().filter(|p| !p.starts_with(&metadata_dir)).collect();
+        if paths.is_empty() {
+            return;
+        }
+        note_vault_change(&app, &id);
+        for path in paths {
+            let payload = json!({ "vaultId": id, "kind": kind, "path": path.to_string_lossy() });
+            if let Err(e) = app.emit("vault-changed", payload) {
+                eprintln!("[vault-changed] failed to emit event: {}", e);
+            }
+        }
+    };
+    let mut watcher = notify::recommended_watcher(handler).map_err(|e| format!("failed to start watcher: {}", e))?;
+    watcher
+        .watch(&root, notify::RecursiveMode::Recursive)
+        .map_err(|e| format!("failed to watch {}: {}", root.display(), e))?;
+    map.insert(vault_id.to_string(), watcher);
+    Ok(())
+}
+
+/// Stop watching a vault. Unknown or unwatched vaults are ignored.
+#[tauri::command]
+fn unwatch_vault(watchers: tauri::State<'_, VaultWatchers>, vault_id: &str) -> Result<(), String> {
+    watchers.0.lock().map_err(|e| e.to_string())?.remove(vault_id);
+    Ok(())
+}
+
+// ----------------- Trash (soft delete) -----------------
+
+/// Index of the trashed items, kept next to them in the trash folder.
+const TRASH_INDEX_FILE: &str = "index.json";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TrashEntry {
+    /// Name of the staged item inside the trash folder (`<timestamp>_<name>`).
+    trashed_name: String,
+    file_id: String,
+    original_path: String,
+    deleted_at_ms: i64,
+}
+
+/// Trash folder of a vault: `<vault>/.focosx/trash` for filesystem vaults,
+/// `trash/<vaultId>` in the app data dir otherwise.
+fn trash_dir(vault_id: &str) -> Result<PathBuf, String> {
+    let mut dir = match find_vault_path(vault_id)?.filter(|p| p.is_absolute()) {
+        Some(mut root) => {
+            root.push(".focosx");
+            root.push("trash");
+            root
+        }
+        None => {
+            let mut base = base_dir()?;
+            base.push("trash");
+            base.push(vault_id);
+            base
+        }
+    };
+    ensure_dir(&dir)?;
+    dir.push(TRASH_INDEX_FILE);
+    if !dir.exists() {
+        // trash folders created before the index was renamed
+        let legacy = dir.with_file_name("trash_manifest.json");
+        if legacy.exists() {
+            fs::rename(&legacy, &dir).map_err(|e| format!("failed to migrate {}: {}", legacy.display(), e))?;
+        } else {
+            write_json_file(&dir, "[]")?;
+        }
+    }
+    dir.pop();
+    Ok(dir)
+}
+
+fn read_trash_manifest(dir: &Path) -> Result<Vec<TrashEntry>, String> {
+    let raw = read_json_file(&dir.join(TRASH_INDEX_FILE))?;
+    if raw.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&raw).map_err(|e| e.to_string())
+}
+
+fn write_trash_manifest(dir: &Path, entries: &[TrashEntry]) -> Result<(), String> {
+    let out = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    write_json_file(&dir.join(TRASH_INDEX_FILE), &out)
+}
+
+/// Move `path` (the node `file_id`) into the vault's trash and record it so
+/// `undo_last_delete` can put it back.
+fn move_to_trash(vault_id: &str, file_id: &str, path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+    let dir = trash_dir(vault_id)?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    // Several nodes with the same name can be trashed within one millisecond
+    // (e.g. by batch_delete_nodes); never let one overwrite another.
+    let mut trashed_name = format!("{}_{}", now, name);
+    let mut n = 1;
+    while dir.join(&trashed_name).exists() {
+        trashed_name = format!("{}_{}_{}", now, n, name);
+        n += 1;
+    }
+    fs::rename(path, dir.join(&trashed_name)).map_err(|e| format!("failed to move {} to trash: {}", path.display(), e))?;
+
+    let mut entries = read_trash_manifest(&dir)?;
+    entries.push(TrashEntry {
+        trashed_name,
+        file_id: file_id.to_string(),
+        original_path: path.to_string_lossy().to_string(),
+        deleted_at_ms: now,
+    });
+    write_trash_manifest(&dir, &entries)
+}
+
+/// Move trash entry `index` back to its original location, drop it from the
+/// index and return its file ID.
+fn restore_trash_entry(app: &tauri::AppHandle, vault_id: &str, dir: &Path, mut entries: Vec<TrashEntry>, index: usize) -> Result<String, String> {
+    let original = PathBuf::from(&entries[index].original_path);
+    if original.exists() {
+        return Err(format!("Cannot restore: {} already exists", original.display()));
+    }
+    if let Some(parent) = original.parent() {
+        ensure_dir(parent)?;
+    }
+    fs::rename(dir.join(&entries[index].trashed_name), &original).map_err(|e| format!("failed to restore {}: {}", original.display(), e))?;
+
+    let entry = entries.remove(index);
+    write_trash_manifest(dir, &entries)?;
+    emit_node_event(app, "node-restored", json!({ "vault_id": vault_id, "file_id": entry.file_id }));
+    Ok(entry.file_id)
+}
+
+/// Restore the most recently deleted node of a vault to its original
+/// location and return its file ID.
+#[tauri::command]
+fn undo_last_delete(app: tauri::AppHandle, vault_id: &str) -> Result<String, String> {
+    let dir = trash_dir(vault_id)?;
+    let entries = read_trash_manifest(&dir)?;
+    let latest = entries
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, e)| e.deleted_at_ms)
+        .map(|(i, _)| i)
+        .ok_or("Trash is empty")?;
+    restore_trash_entry(&app, vault_id, &dir, entries, latest)
+}
+
+/// Restore one trashed item by its name inside the trash folder
+/// (`<timestamp>_<name>`) and return its file ID.
+#[tauri::command]
+fn restore_from_trash(app: tauri::AppHandle, vault_id: &str, trash_entry_name: &str) -> Result<String, String> {
+    let dir = trash_dir(vault_id)?;
+    let entries = read_trash_manifest(&dir)?;
+    let index = entries
+        .iter()
+        .position(|e| e.trashed_name == trash_entry_name)
+        .ok_or_else(|| format!("not in trash: {}", trash_entry_name))?;
+    restore_trash_entry(&app, vault_id, &dir, entries, index)
+}
+
+/// Items in a vault's trash, newest first, as
+/// `[{ trashName, originalPath, deletedAtMs, isDir, sizeBytes }]`.
+#[tauri::command]
+fn list_trash_entries(vault_id: &str) -> Result<String, String> {
+    let dir = trash_dir(vault_id)?;
+    let mut entries = read_trash_manifest(&dir)?;
+    entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at_ms));
+    let out: Vec<serde_json::Value> = entries
+        .iter()
+        .map(|e| {
+            let path = dir.join(&e.trashed_name);
+            let is_dir = path.is_dir();
+            let size = if is_dir { dir_size(&path) } else { fs::metadata(&path).map(|m| m.len()).unwrap_or(0) };
+            json!({
+                "trashName": e.trashed_name,
+                "originalPath": e.original_path,
+                "deletedAtMs": e.deleted_at_ms,
+                "isDir": is_dir,
+                "sizeBytes": size,
+            })
+        })
+        .collect();
+    serde_json::to_string(&out).map_err(|e| e.to_string())
+}
+
+/// Permanently delete everything in a vault's trash.
+#[tauri::command]
+fn empty_trash(vault_id: &str) -> Result<(), String> {
+    let dir = trash_dir(vault_id)?;
+    for entry in read_trash_manifest(&dir)? {
+        let path = dir.join(&entry.trashed_name);
+        let res = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        if let Err(e) = res {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(format!("failed to delete {}: {}", path.display(), e));
+            }
+        }
+    }
+    write_trash_manifest(&dir, &[])
+}
+
 // ----------------- Tauri builder -----------------
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -885,24 +5666,97 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .manage(RecentFilesCache::default())
+        .manage(VaultCleanupRegistry::default())
+        .manage(VaultChangeLog::default())
+        .manage(ScanCancellation::default())
+        .manage(VaultWatchers::default())
         .invoke_handler(tauri::generate_handler![
             greet,
             ping,
+            validate_json,
             // vaults
             get_vaults,
             save_vaults,
             // vault folder selection / external-path support
             select_vault_folder,
             create_vault_at_path,
+            rename_vault,
+            get_vault_last_opened,
+            update_vault_last_opened,
+            get_emoji_for_vault,
+            set_emoji_for_vault,
+            get_vault_description,
+            set_vault_description,
+            clone_vault_structure,
+            copy_vault,
+            get_vault_disk_path,
             // trees
             load_tree,
             save_tree,
+            cancel_vault_scan,
+            watch_vault,
+            unwatch_vault,
+            load_vault_config,
+            save_vault_config,
+            get_vault_folder_structure,
+            get_tree_stats,
+            get_vault_stats,
+            vault_health_check,
+            cleanup_orphaned_content,
+            scan_directory_cmd,
+            resolve_vault_relative_path,
+            get_absolute_path_for_node,
+            get_node_metadata,
+            get_recently_modified_nodes,
+            record_recently_opened,
+            get_recently_opened,
+            set_bookmarked_node,
+            get_bookmarked_nodes,
+            list_canvas_files,
+            full_text_search_vault,
+            regex_search_vault,
+            detect_duplicate_files,
+            export_vault_as_zip,
+            import_from_obsidian_vault,
+            generate_vault_readme,
             // trees stored inside user vault folder (absolute path)
             load_tree_from_vault_path,
             save_tree_to_vault_path,
             // contents
             load_file_content,
             save_file_content,
+            get_file_preview,
+            replace_in_file,
+            file_content_hash,
+            get_file_versions,
+            get_file_version_content,
+            restore_file_version,
+            // storage quota
+            set_vault_storage_limit,
+            get_storage_quota_usage,
+            // sync
+            get_vault_sync_manifest,
+            // note frontmatter
+            get_note_aliases,
+            set_note_aliases,
+            extract_frontmatter,
+            // text analysis
+            get_note_word_frequency,
+            get_text_statistics,
+            get_content_stats,
+            // templates
+            create_template_from_file,
+            list_templates,
+            // links
+            extract_markdown_links,
+            build_backlink_index,
+            extract_markdown_headings,
+            get_linked_files,
+            get_vault_note_names,
+            get_vault_orphaned_files,
+            // lint
+            lint_vault,
             // arbitrary file read/write inside vault or absolute path
             load_file_from_absolute_path,
             save_file_to_absolute_path,
@@ -914,24 +5768,62 @@ pub fn run() {
             get_installed_remote_plugins,
             save_installed_remote_plugin,
             remove_installed_remote_plugin,
+            check_remote_plugin_updates,
+            get_plugin_config,
+            save_plugin_config,
+            delete_plugin_config,
+            enable_plugin,
+            disable_plugin,
+            get_all_installed_plugins,
+            increment_plugin_usage,
+            get_plugin_usage_stats,
             // ai dock
             get_ai_dock_config,
             save_ai_dock_config,
+            save_ai_conversation,
+            load_ai_conversation,
+            list_ai_conversations,
+            delete_ai_conversation,
+            // editor state
+            get_editor_state,
+            save_editor_state,
+            clear_editor_state,
+            clear_all_editor_states,
             // prefs
             get_preference,
             save_preference,
+            delete_preference,
+            get_all_preferences,
+            save_preferences_bulk,
+            export_preferences,
+            import_preferences,
+            export_preferences_as_dotenv,
+            restore_preferences_from_backup,
+            // caches
+            flush_all_caches,
             // vault cleanup
             delete_vault,
+            register_vault_cleanup_path,
             // generic fs utils
             read_text_file_cmd,
             write_text_file_cmd,
             create_dir_cmd,
             list_dir_cmd,
+            list_dir_paths_cmd,
             remove_path_cmd,
+            open_path_in_file_manager,
             // granular node ops
             create_node_cmd,
             delete_node_cmd,
-            rename_node_cmd
+            batch_delete_nodes,
+            undo_last_delete,
+            restore_from_trash,
+            list_trash_entries,
+            empty_trash,
+            rename_node_cmd,
+            rename_node_and_update_links,
+            move_node_cmd,
+            copy_node_cmd
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
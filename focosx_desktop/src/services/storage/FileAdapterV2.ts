@@ -99,9 +99,10 @@ export class FileAdapterV2 implements IStorageAdapter {
       const raw = await invoke('get_vaults');
       const arr = raw && raw.length ? JSON.parse(raw) : [];
       const filtered = arr.filter((v: any) => v.id !== id);
+      // delete_vault needs the vault's entry to find its folder, so run it first
+      await invoke('delete_vault', { vaultId: id });
       await invoke('save_vaults', { json: JSON.stringify(filtered) });
       try { localStorage.setItem(this.VAULTS_KEY, JSON.stringify(filtered)); } catch {}
-      await invoke('delete_vault', { vaultId: id });
       this.write(this.VAULTS_KEY, JSON.stringify(filtered));
     } catch {
       const raw = this.read(this.VAULTS_KEY);
@@ -129,7 +130,7 @@ export class FileAdapterV2 implements IStorageAdapter {
   async saveWorkspacePluginIds(vaultId: string, ids: string[]): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('save_workspace_plugin_ids', { vaultId, json: JSON.stringify(ids) }); this.write(`${this.WORKSPACE_PLUGINS_PREFIX}${vaultId}`, JSON.stringify(ids)); } catch { this.write(`${this.WORKSPACE_PLUGINS_PREFIX}${vaultId}`, JSON.stringify(ids)); } }
 
   async getInstalledRemotePlugins(): Promise<{ id: string; code: string; manifestUrl: string }[]> { try { const invoke = await this.getInvoke(); const raw = await invoke('get_installed_remote_plugins'); return raw && raw.length ? JSON.parse(raw) : []; } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); return raw && raw.length ? JSON.parse(raw) : []; } }
-  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string }): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('save_installed_remote_plugin', { json: JSON.stringify(plugin) }); } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); const arr = raw && raw.length ? JSON.parse(raw) : []; const idx = arr.findIndex((p: any) => p.id === plugin.id); if (idx >= 0) arr[idx] = plugin; else arr.push(plugin); this.write(this.REMOTE_PLUGINS_KEY, JSON.stringify(arr)); } }
+  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string; version?: string }): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('save_installed_remote_plugin', { json: JSON.stringify(plugin) }); } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); const arr = raw && raw.length ? JSON.parse(raw) : []; const idx = arr.findIndex((p: any) => p.id === plugin.id); if (idx >= 0) arr[idx] = plugin; else arr.push(plugin); this.write(this.REMOTE_PLUGINS_KEY, JSON.stringify(arr)); } }
   async removeInstalledRemotePlugin(id: string): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('remove_installed_remote_plugin', { id }); } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); if (!raw) return; const arr = JSON.parse(raw).filter((p: any) => p.id !== id); this.write(this.REMOTE_PLUGINS_KEY, JSON.stringify(arr)); } }
 
   async getAIDockConfig(): Promise<{ url: string; name: string } | null> { try { const invoke = await this.getInvoke(); const raw = await invoke('get_ai_dock_config'); return raw && raw.length ? JSON.parse(raw) : null; } catch { const raw = this.read(this.AI_DOCK_CONFIG_KEY); return raw && raw.length ? JSON.parse(raw) : null; } }
@@ -23,6 +23,7 @@ export interface IStorageAdapter {
     id: string;
     code: string;
     manifestUrl: string;
+    version?: string;
   }): Promise<void>;
   removeInstalledRemotePlugin(id: string): Promise<void>;
 
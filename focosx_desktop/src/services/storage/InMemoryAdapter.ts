@@ -100,7 +100,7 @@ export class InMemoryAdapter implements IStorageAdapter {
     const raw = this.read(this.REMOTE_PLUGINS_KEY);
     return raw && raw.length ? JSON.parse(raw) : [];
   }
-  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string }): Promise<void> {
+  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string; version?: string }): Promise<void> {
     const raw = this.read(this.REMOTE_PLUGINS_KEY);
     const arr = raw && raw.length ? JSON.parse(raw) : [];
     const idx = arr.findIndex((p: any) => p.id === plugin.id);
@@ -66,9 +66,10 @@ export class TauriStorageAdapter implements IStorageAdapter {
     const raw = await invoke("get_vaults");
     const arr = raw && raw.length ? JSON.parse(raw) : [];
     const filtered = arr.filter((v: any) => v.id !== id);
+    // delete_vault needs the vault's entry to find its folder, so run it first
+    await invoke("delete_vault", { vaultId: id });
     await invoke("save_vaults", { json: JSON.stringify(filtered) });
     try { localStorage.setItem("focosx_vaults", JSON.stringify(filtered)); } catch {}
-    await invoke("delete_vault", { vaultId: id });
   }
 
   async loadTree(vaultId: string): Promise<FileSystemNode[]> {
@@ -123,7 +124,7 @@ export class TauriStorageAdapter implements IStorageAdapter {
     const raw = await invoke("get_installed_remote_plugins");
     return raw && raw.length ? JSON.parse(raw) : [];
   }
-  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string }): Promise<void> {
+  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string; version?: string }): Promise<void> {
     const invoke = await this.invokeFn();
     await invoke("save_installed_remote_plugin", { json: JSON.stringify(plugin) });
   }
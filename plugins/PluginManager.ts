@@ -171,7 +171,8 @@ class PluginManagerImpl {
           await storage.saveInstalledRemotePlugin({
               id: pluginDef.id,
               code: code,
-              manifestUrl: manifest.scriptUrl
+              manifestUrl: manifest.scriptUrl,
+              version: manifest.version
           });
           
           // 4. Cache in Memory
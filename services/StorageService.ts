@@ -30,6 +30,7 @@ export interface IStorageAdapter {
     id: string;
     code: string;
     manifestUrl: string;
+    version?: string;
   }): Promise<void>;
   removeInstalledRemotePlugin(id: string): Promise<void>;
 
@@ -165,6 +166,7 @@ class LocalStorageAdapter implements IStorageAdapter {
     id: string;
     code: string;
     manifestUrl: string;
+    version?: string;
   }): Promise<void> {
     const current = await this.getInstalledRemotePlugins();
     const existingIdx = current.findIndex((p) => p.id === plugin.id);
@@ -369,6 +371,7 @@ class InMemoryAdapter implements IStorageAdapter {
     id: string;
     code: string;
     manifestUrl: string;
+    version?: string;
   }): Promise<void> {
     const raw = this.read(this.REMOTE_PLUGINS_KEY);
     const arr = raw && raw.length ? JSON.parse(raw) : [];
@@ -515,8 +518,8 @@ class FileAdapterV2 implements IStorageAdapter {
       const raw = await invoke('get_vaults');
       const arr = raw && raw.length ? JSON.parse(raw) : [];
       const filtered = arr.filter((v: any) => v.id !== id);
-      await invoke('save_vaults', { json: JSON.stringify(filtered) });
       await invoke('delete_vault', { vault_id: id });
+      await invoke('save_vaults', { json: JSON.stringify(filtered) });
       this.write(this.VAULTS_KEY, JSON.stringify(filtered));
     } catch {
       const raw = this.read(this.VAULTS_KEY);
@@ -543,7 +546,7 @@ class FileAdapterV2 implements IStorageAdapter {
   async saveWorkspacePluginIds(vaultId: string, ids: string[]): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('save_workspace_plugin_ids', { vault_id: vaultId, json: JSON.stringify(ids) }); this.write(`${this.WORKSPACE_PLUGINS_PREFIX}${vaultId}`, JSON.stringify(ids)); } catch { this.write(`${this.WORKSPACE_PLUGINS_PREFIX}${vaultId}`, JSON.stringify(ids)); } }
 
   async getInstalledRemotePlugins(): Promise<{ id: string; code: string; manifestUrl: string }[]> { try { const invoke = await this.getInvoke(); const raw = await invoke('get_installed_remote_plugins'); return raw && raw.length ? JSON.parse(raw) : []; } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); return raw && raw.length ? JSON.parse(raw) : []; } }
-  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string }): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('save_installed_remote_plugin', { json: JSON.stringify(plugin) }); } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); const arr = raw && raw.length ? JSON.parse(raw) : []; const idx = arr.findIndex((p: any) => p.id === plugin.id); if (idx >= 0) arr[idx] = plugin; else arr.push(plugin); this.write(this.REMOTE_PLUGINS_KEY, JSON.stringify(arr)); } }
+  async saveInstalledRemotePlugin(plugin: { id: string; code: string; manifestUrl: string; version?: string }): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('save_installed_remote_plugin', { json: JSON.stringify(plugin) }); } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); const arr = raw && raw.length ? JSON.parse(raw) : []; const idx = arr.findIndex((p: any) => p.id === plugin.id); if (idx >= 0) arr[idx] = plugin; else arr.push(plugin); this.write(this.REMOTE_PLUGINS_KEY, JSON.stringify(arr)); } }
   async removeInstalledRemotePlugin(id: string): Promise<void> { try { const invoke = await this.getInvoke(); await invoke('remove_installed_remote_plugin', { id }); } catch { const raw = this.read(this.REMOTE_PLUGINS_KEY); if (!raw) return; const arr = JSON.parse(raw).filter((p: any) => p.id !== id); this.write(this.REMOTE_PLUGINS_KEY, JSON.stringify(arr)); } }
 
   async getAIDockConfig(): Promise<{ url: string; name: string } | null> { try { const invoke = await this.getInvoke(); const raw = await invoke('get_ai_dock_config'); return raw && raw.length ? JSON.parse(raw) : null; } catch { const raw = this.read(this.AI_DOCK_CONFIG_KEY); return raw && raw.length ? JSON.parse(raw) : null; } }
@@ -614,8 +617,8 @@ class TauriAdapter implements IStorageAdapter {
     const raw = await invoke("get_vaults");
     const arr = raw && raw.length ? JSON.parse(raw) : [];
     const filtered = arr.filter((v: any) => v.id !== id);
-    await invoke("save_vaults", { json: JSON.stringify(filtered) });
     await invoke("delete_vault", { vault_id: id });
+    await invoke("save_vaults", { json: JSON.stringify(filtered) });
   }
   async loadTree(vaultId: string): Promise<FileSystemNode[]> {
     const invoke = await this.invokeFn();
@@ -680,6 +683,7 @@ class TauriAdapter implements IStorageAdapter {
     id: string;
     code: string;
     manifestUrl: string;
+    version?: string;
   }): Promise<void> {
     const invoke = await this.invokeFn();
     await invoke("save_installed_remote_plugin", {
@@ -1010,6 +1014,7 @@ class ProxyStorage implements IStorageAdapter {
     id: string;
     code: string;
     manifestUrl: string;
+    version?: string;
   }): Promise<void> {
     return this.exec((d) => d.saveInstalledRemotePlugin(plugin));
   }